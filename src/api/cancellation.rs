@@ -0,0 +1,74 @@
+use super::DisposableClosure;
+use crate::sys::{CancellationToken, IDisposable};
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: bool,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [`cancellable`].
+pub(crate) struct Cancellable<F> {
+    future: Pin<Box<F>>,
+    state: Rc<RefCell<CancelState>>,
+    _listener: Option<DisposableClosure<dyn FnMut(JsValue)>>,
+}
+impl<F: Future> Future for Cancellable<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        {
+            let mut state = self.state.borrow_mut();
+            if state.cancelled {
+                return Poll::Ready(None);
+            }
+            state.waker = Some(cx.waker().clone());
+        }
+        self.future.as_mut().poll(cx).map(Some)
+    }
+}
+
+/// Drive `future` until it completes or `token` requests cancellation.
+///
+/// Requesting cancellation wakes the task, which then resolves to `None`
+/// without polling `future` again. The future itself is dropped together with
+/// the returned [`Cancellable`].
+pub(crate) fn cancellable<F: Future>(token: &CancellationToken, future: F) -> Cancellable<F> {
+    let state = Rc::new(RefCell::new(CancelState {
+        cancelled: token.is_cancellation_requested(),
+        waker: None,
+    }));
+
+    let listener = if state.borrow().cancelled {
+        None
+    } else {
+        let listener_state = Rc::clone(&state);
+        let cb = Closure::wrap(Box::new(move |_: JsValue| {
+            let mut state = listener_state.borrow_mut();
+            state.cancelled = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }) as Box<dyn FnMut(JsValue)>);
+        let js_disposable = token
+            .on_cancellation_requested()
+            .call1(token, cb.as_ref())
+            .expect("subscribing to cancellation token must not fail")
+            .unchecked_into::<IDisposable>();
+        Some(DisposableClosure::new(cb, js_disposable))
+    };
+
+    Cancellable {
+        future: Box::pin(future),
+        state,
+        _listener: listener,
+    }
+}
@@ -0,0 +1,123 @@
+use super::TextModel;
+use crate::sys::{
+    editor::{self, IColorizerElementOptions, IColorizerOptions},
+    Token,
+};
+use js_sys::Array;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlElement;
+
+/// Options for [`colorize_element`].
+/// This is the typed version of [`IColorizerElementOptions`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ColorizerElementOptions {
+    pub tab_size: Option<u32>,
+    /// Theme to use while colorizing.
+    pub theme: Option<String>,
+    /// Mime type of the contents. Only used if the element doesn't have a
+    /// `data-lang` attribute.
+    pub mime_type: Option<String>,
+}
+impl ColorizerElementOptions {
+    builder_methods! {
+        pub with tab_size(u32);
+        pub with theme(String);
+        pub with mime_type(String);
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert into [`IColorizerElementOptions`].
+    pub fn to_sys_options(&self) -> IColorizerElementOptions {
+        // this helps ensure we don't miss any members
+        let Self {
+            tab_size,
+            theme,
+            mime_type,
+        } = self;
+
+        let options = IColorizerElementOptions::default();
+        let base: &IColorizerOptions = options.as_ref();
+        base.set_tab_size(tab_size.map(f64::from));
+        options.set_theme(theme.as_deref());
+        options.set_mime_type(mime_type.as_deref());
+        options
+    }
+}
+
+impl From<&ColorizerElementOptions> for IColorizerElementOptions {
+    fn from(options: &ColorizerElementOptions) -> Self {
+        options.to_sys_options()
+    }
+}
+
+/// Colorize `text` using the language `language_id`.
+///
+/// Resolves to the HTML of the colorized text.
+pub async fn colorize(
+    text: &str,
+    language_id: &str,
+    tab_size: Option<u32>,
+) -> Result<String, JsValue> {
+    let options = IColorizerOptions::default();
+    options.set_tab_size(tab_size.map(f64::from));
+    let html = JsFuture::from(editor::colorize(text, language_id, &options)).await?;
+    Ok(html.as_string().unwrap_or_default())
+}
+
+/// Colorize the contents of `element` using its `data-lang` attribute.
+pub async fn colorize_element(
+    element: &HtmlElement,
+    options: &ColorizerElementOptions,
+) -> Result<(), JsValue> {
+    JsFuture::from(editor::colorize_element(element, &options.to_sys_options()))
+        .await
+        .map(|_| ())
+}
+
+/// Colorize a line of `model`.
+///
+/// Returns the HTML of the colorized line.
+pub fn colorize_model_line(model: &TextModel, line_number: u32, tab_size: Option<u32>) -> String {
+    editor::colorize_model_line(model.as_ref(), line_number.into(), tab_size.map(f64::from))
+}
+
+/// A token returned by [`tokenize`].
+/// This is the Rust version of [`Token`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TokenSpan {
+    /// Offset of the first character of the token in the line.
+    pub offset: u32,
+    /// Type of the token, for example `"keyword.rust"`.
+    pub kind: String,
+    /// Language of the token.
+    pub language: String,
+}
+
+impl From<&Token> for TokenSpan {
+    fn from(token: &Token) -> Self {
+        Self {
+            offset: token.offset() as u32,
+            kind: token.type_(),
+            language: token.language(),
+        }
+    }
+}
+
+/// Tokenize `text` using the language `language_id`.
+///
+/// Returns the tokens of each line.
+pub fn tokenize(text: &str, language_id: &str) -> Vec<Vec<TokenSpan>> {
+    editor::tokenize(text, language_id)
+        .iter()
+        .map(|line| {
+            line.unchecked_into::<Array>()
+                .iter()
+                .map(|token| TokenSpan::from(token.unchecked_ref::<Token>()))
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+use super::TextModel;
+use crate::sys::{
+    editor::{IModelDecorationOptions, IModelDeltaDecoration},
+    IRange,
+    Range,
+};
+use js_sys::{Array, Object};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Styling applied to a range of a model.
+/// This is a simplified version of [`IModelDeltaDecoration`].
+///
+/// Apply them using [`TextModel::delta_decorations`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Decoration {
+    pub start_line_number: u32,
+    pub start_column: u32,
+    pub end_line_number: u32,
+    pub end_column: u32,
+    /// Whether the decoration covers entire lines.
+    pub is_whole_line: Option<bool>,
+    /// CSS class applied to the lines of the decoration.
+    pub class_name: Option<String>,
+    /// CSS class applied to the text of the decoration.
+    pub inline_class_name: Option<String>,
+    /// CSS class applied to the glyph margin next to the decoration.
+    pub glyph_margin_class_name: Option<String>,
+    /// Markdown shown when hovering over the decoration.
+    pub hover_message: Option<String>,
+}
+impl Decoration {
+    builder_methods! {
+        pub with is_whole_line(bool);
+        pub with class_name(String);
+        pub with inline_class_name(String);
+        pub with glyph_margin_class_name(String);
+        pub with hover_message(String);
+    }
+
+    pub fn new(
+        start_line_number: u32,
+        start_column: u32,
+        end_line_number: u32,
+        end_column: u32,
+    ) -> Self {
+        Self {
+            start_line_number,
+            start_column,
+            end_line_number,
+            end_column,
+            ..Self::default()
+        }
+    }
+
+    /// Convert into [`IModelDeltaDecoration`].
+    pub fn to_sys_decoration(&self) -> IModelDeltaDecoration {
+        // this helps ensure we don't miss any members
+        let Self {
+            start_line_number,
+            start_column,
+            end_line_number,
+            end_column,
+            is_whole_line,
+            class_name,
+            inline_class_name,
+            glyph_margin_class_name,
+            hover_message,
+        } = self;
+
+        let options = IModelDecorationOptions::default();
+        options.set_is_whole_line(*is_whole_line);
+        options.set_class_name(class_name.as_deref());
+        options.set_inline_class_name(inline_class_name.as_deref());
+        options.set_glyph_margin_class_name(glyph_margin_class_name.as_deref());
+        if let Some(hover_message) = hover_message {
+            let markdown = Object::new();
+            object_set!(markdown.value = hover_message.as_str());
+            options.set_hover_message(&markdown);
+        }
+
+        let range: IRange = Range::new(
+            (*start_line_number).into(),
+            (*start_column).into(),
+            (*end_line_number).into(),
+            (*end_column).into(),
+        )
+        .unchecked_into();
+
+        let decoration: IModelDeltaDecoration = Object::new().unchecked_into();
+        decoration.set_range(&range);
+        decoration.set_options(&options);
+        decoration
+    }
+}
+
+impl From<&Decoration> for IModelDeltaDecoration {
+    fn from(decoration: &Decoration) -> Self {
+        decoration.to_sys_decoration()
+    }
+}
+
+impl TextModel {
+    /// Remove the decorations with `old_ids` and add `decorations`.
+    ///
+    /// Returns the ids of the added decorations, which are needed to remove
+    /// them again.
+    pub fn delta_decorations(&self, old_ids: &[String], decorations: &[Decoration]) -> Vec<String> {
+        let old_ids = old_ids
+            .iter()
+            .map(|id| JsValue::from_str(id))
+            .collect::<Array>();
+        let decorations = decorations
+            .iter()
+            .map(Decoration::to_sys_decoration)
+            .collect::<Array>();
+        self.as_ref()
+            .delta_decorations(&old_ids, &decorations, None)
+            .iter()
+            .filter_map(|id| id.as_string())
+            .collect()
+    }
+}
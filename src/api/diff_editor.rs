@@ -0,0 +1,420 @@
+use super::{CodeEditor, DisposableClosure, TextModel};
+use crate::sys::editor::{
+    self,
+    BuiltinTheme,
+    IChange,
+    ICharChange,
+    IDiffEditorConstructionOptions,
+    IDiffEditorModel,
+    IDiffEditorOptions,
+    IDiffNavigator,
+    IDiffNavigatorOptions,
+    ILineChange,
+    IStandaloneDiffEditor,
+};
+use js_sys::Object;
+use std::{borrow::Borrow, cell::Cell, mem::ManuallyDrop, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlElement;
+
+/// Options for creating a new diff editor. This represents a simplified
+/// version of [`IDiffEditorConstructionOptions`].
+///
+/// If you need an option that isn't present you can use
+/// [`to_sys_options`](Self::to_sys_options) to
+/// build the [`IDiffEditorConstructionOptions`] object and expand it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiffEditorOptions {
+    pub theme: Option<String>,
+    /// Render the differences in two side-by-side editors instead of inline.
+    pub render_side_by_side: Option<bool>,
+    pub enable_split_view_resizing: Option<bool>,
+    pub ignore_trim_whitespace: Option<bool>,
+    pub original_editable: Option<bool>,
+    pub read_only: Option<bool>,
+    pub automatic_layout: Option<bool>,
+}
+impl DiffEditorOptions {
+    builder_methods! {
+        pub with theme(String);
+        pub with render_side_by_side(bool);
+        pub with enable_split_view_resizing(bool);
+        pub with ignore_trim_whitespace(bool);
+        pub with original_editable(bool);
+        pub with read_only(bool);
+        pub with automatic_layout(bool);
+    }
+
+    pub fn with_builtin_theme(self, theme: BuiltinTheme) -> Self {
+        self.with_theme(theme.to_value().to_owned())
+    }
+
+    /// Convert into [`IDiffEditorConstructionOptions`].
+    pub fn to_sys_options(&self) -> IDiffEditorConstructionOptions {
+        let options = IDiffEditorConstructionOptions::default();
+
+        // this helps ensure we don't miss any members
+        let DiffEditorOptions {
+            theme,
+            render_side_by_side,
+            enable_split_view_resizing,
+            ignore_trim_whitespace,
+            original_editable,
+            read_only,
+            automatic_layout,
+        } = self;
+
+        options.set_theme(theme.as_deref());
+        options.set_render_side_by_side(*render_side_by_side);
+        options.set_enable_split_view_resizing(*enable_split_view_resizing);
+        options.set_ignore_trim_whitespace(*ignore_trim_whitespace);
+        options.set_original_editable(*original_editable);
+        options.set_read_only(*read_only);
+        options.set_automatic_layout(*automatic_layout);
+
+        options
+    }
+}
+
+impl From<DiffEditorOptions> for IDiffEditorConstructionOptions {
+    fn from(options: DiffEditorOptions) -> Self {
+        options.to_sys_options()
+    }
+}
+
+/// A character level change inside of a [`LineChange`].
+/// This is the typed version of [`ICharChange`].
+///
+/// Line numbers and columns start at 1.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CharChange {
+    pub original_start_line_number: u32,
+    pub original_start_column: u32,
+    pub original_end_line_number: u32,
+    pub original_end_column: u32,
+    pub modified_start_line_number: u32,
+    pub modified_start_column: u32,
+    pub modified_end_line_number: u32,
+    pub modified_end_column: u32,
+}
+impl From<&ICharChange> for CharChange {
+    fn from(change: &ICharChange) -> Self {
+        let lines: &IChange = change.as_ref();
+        Self {
+            original_start_line_number: lines.original_start_line_number() as u32,
+            original_start_column: change.original_start_column() as u32,
+            original_end_line_number: lines.original_end_line_number() as u32,
+            original_end_column: change.original_end_column() as u32,
+            modified_start_line_number: lines.modified_start_line_number() as u32,
+            modified_start_column: change.modified_start_column() as u32,
+            modified_end_line_number: lines.modified_end_line_number() as u32,
+            modified_end_column: change.modified_end_column() as u32,
+        }
+    }
+}
+
+/// A change of a range of lines computed by a [`DiffEditor`].
+/// This is the typed version of [`ILineChange`].
+///
+/// Line numbers start at 1. An end line number of 0 means the range is empty:
+/// the lines were inserted after (or removed after) the start line.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LineChange {
+    pub original_start_line_number: u32,
+    pub original_end_line_number: u32,
+    pub modified_start_line_number: u32,
+    pub modified_end_line_number: u32,
+    /// Empty if the diff editor doesn't compute character level changes for
+    /// this change.
+    pub char_changes: Vec<CharChange>,
+}
+impl From<&ILineChange> for LineChange {
+    fn from(change: &ILineChange) -> Self {
+        let lines: &IChange = change.as_ref();
+        let char_changes = change
+            .char_changes()
+            .map(|changes| {
+                changes
+                    .iter()
+                    .map(|change| CharChange::from(change.unchecked_ref::<ICharChange>()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            original_start_line_number: lines.original_start_line_number() as u32,
+            original_end_line_number: lines.original_end_line_number() as u32,
+            modified_start_line_number: lines.modified_start_line_number() as u32,
+            modified_end_line_number: lines.modified_end_line_number() as u32,
+            char_changes,
+        }
+    }
+}
+
+/// Monaco diff editor.
+///
+/// This struct should be the sole owner of the underlying
+/// [`IStandaloneDiffEditor`] because it will call
+/// [`dispose`](crate::sys::editor::IEditor::dispose) when dropped.
+/// This is only an issue when using the [`From`] trait.
+#[must_use = "editor is disposed when dropped"]
+#[derive(Debug)]
+pub struct DiffEditor {
+    js_editor: IStandaloneDiffEditor,
+    // owned by the diff editor, so they must never be disposed on their own
+    original: ManuallyDrop<CodeEditor>,
+    modified: ManuallyDrop<CodeEditor>,
+}
+impl DiffEditor {
+    event_methods! {
+        /// An event emitted when the diff information computed by this diff editor has been updated.
+        pub on_did_update_diff(FnMut());
+        /// An event emitted when the editor has been disposed.
+        pub on_did_dispose(FnMut());
+    }
+
+    /// Create a new diff editor under `element`.
+    /// `element` should be empty (not contain other dom nodes).
+    /// The editor will read the size of `element`.
+    pub fn create<OPT>(element: &HtmlElement, options: Option<OPT>) -> Self
+    where
+        OPT: Into<IDiffEditorConstructionOptions>,
+    {
+        #[cfg(feature = "workers")]
+        crate::workers::ensure_environment_set();
+
+        let ioptions = options.map(|x| x.into());
+        let options = ioptions.as_ref().map(Borrow::borrow);
+        let js_editor = editor::create_diff_editor(element, options, None);
+        Self::from(js_editor)
+    }
+
+    /// The editor showing the original model.
+    ///
+    /// It's owned by the diff editor and disposed together with it.
+    pub fn original_editor(&self) -> &CodeEditor {
+        &self.original
+    }
+
+    /// The editor showing the modified model.
+    ///
+    /// It's owned by the diff editor and disposed together with it.
+    pub fn modified_editor(&self) -> &CodeEditor {
+        &self.modified
+    }
+
+    /// Gets the original and modified models attached to this editor.
+    pub fn get_model(&self) -> Option<(TextModel, TextModel)> {
+        self.js_editor
+            .get_model()
+            .map(|model| (model.original().into(), model.modified().into()))
+    }
+
+    /// Compare `original` with `modified`.
+    ///
+    /// The models aren't disposed when they're replaced or when the editor is
+    /// dropped.
+    pub fn set_model(&self, original: &TextModel, modified: &TextModel) {
+        let model: IDiffEditorModel = Object::new().unchecked_into();
+        model.set_original(original.as_ref());
+        model.set_modified(modified.as_ref());
+        self.js_editor.set_model(Some(&model));
+    }
+
+    /// Detaches the current models from the editor and returns them.
+    pub fn detach_model(&self) -> Option<(TextModel, TextModel)> {
+        let model = self.get_model();
+        self.js_editor.set_model(None);
+        model
+    }
+
+    /// Get the computed diff.
+    ///
+    /// Returns `None` if the diff hasn't been computed yet, see
+    /// [`on_did_update_diff`](Self::on_did_update_diff).
+    pub fn line_changes(&self) -> Option<Vec<LineChange>> {
+        self.js_editor.get_line_changes().map(|changes| {
+            changes
+                .iter()
+                .map(|change| LineChange::from(change.unchecked_ref::<ILineChange>()))
+                .collect()
+        })
+    }
+
+    /// Show the differences in two side-by-side editors if `side_by_side` is
+    /// `true`, otherwise show them inline in a single editor.
+    pub fn set_render_side_by_side(&self, side_by_side: bool) {
+        let options: IDiffEditorOptions = Object::new().unchecked_into();
+        options.set_render_side_by_side(Some(side_by_side));
+        self.js_editor.update_options(&options);
+    }
+
+    /// Update the editor's options after the editor has been created.
+    pub fn update_options(&self, options: &IDiffEditorOptions) {
+        self.js_editor.update_options(options);
+    }
+}
+impl Drop for DiffEditor {
+    fn drop(&mut self) {
+        self.js_editor.dispose();
+    }
+}
+
+impl AsRef<IStandaloneDiffEditor> for DiffEditor {
+    fn as_ref(&self) -> &IStandaloneDiffEditor {
+        &self.js_editor
+    }
+}
+impl From<IStandaloneDiffEditor> for DiffEditor {
+    fn from(js_editor: IStandaloneDiffEditor) -> Self {
+        let original = CodeEditor::from(js_editor.get_original_editor());
+        let modified = CodeEditor::from(js_editor.get_modified_editor());
+        Self {
+            js_editor,
+            original: ManuallyDrop::new(original),
+            modified: ManuallyDrop::new(modified),
+        }
+    }
+}
+
+/// Options for creating a [`DiffNavigator`]. This is the typed version of
+/// [`IDiffNavigatorOptions`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiffNavigatorOptions {
+    /// Whether the navigator continues from the cursor position instead of
+    /// the last revealed change.
+    pub follows_caret: Option<bool>,
+    /// Navigate between line changes only, skipping the character changes
+    /// inside of them.
+    pub ignore_char_changes: Option<bool>,
+    /// Reveal the first change as soon as the diff is computed.
+    pub always_reveal_first: Option<bool>,
+}
+impl DiffNavigatorOptions {
+    builder_methods! {
+        pub with follows_caret(bool);
+        pub with ignore_char_changes(bool);
+        pub with always_reveal_first(bool);
+    }
+
+    /// Convert into [`IDiffNavigatorOptions`].
+    pub fn to_sys_options(&self) -> IDiffNavigatorOptions {
+        let options = IDiffNavigatorOptions::default();
+
+        // this helps ensure we don't miss any members
+        let DiffNavigatorOptions {
+            follows_caret,
+            ignore_char_changes,
+            always_reveal_first,
+        } = self;
+
+        if let Some(follows_caret) = *follows_caret {
+            object_set!(options.followsCaret = follows_caret);
+        }
+        if let Some(ignore_char_changes) = *ignore_char_changes {
+            object_set!(options.ignoreCharChanges = ignore_char_changes);
+        }
+        if let Some(always_reveal_first) = *always_reveal_first {
+            object_set!(options.alwaysRevealFirst = always_reveal_first);
+        }
+
+        options
+    }
+}
+
+impl From<DiffNavigatorOptions> for IDiffNavigatorOptions {
+    fn from(options: DiffNavigatorOptions) -> Self {
+        options.to_sys_options()
+    }
+}
+
+/// Moves between the changes of a [`DiffEditor`].
+///
+/// The navigator is disposed when dropped. It keeps its own handle to the diff
+/// editor so it can be stored next to it, but once the editor is disposed the
+/// navigator is too and navigating does nothing.
+#[must_use = "navigator is disposed when dropped"]
+#[derive(Debug)]
+pub struct DiffNavigator {
+    js_navigator: IDiffNavigator,
+    js_editor: IStandaloneDiffEditor,
+    disposed: Rc<Cell<bool>>,
+    _on_editor_disposed: DisposableClosure<dyn FnMut()>,
+}
+impl DiffNavigator {
+    /// Create a navigator for the changes of `editor`.
+    pub fn create<OPT>(editor: &DiffEditor, options: Option<OPT>) -> Self
+    where
+        OPT: Into<IDiffNavigatorOptions>,
+    {
+        let ioptions = options.map(|x| x.into());
+        let options = ioptions.as_ref().map(Borrow::borrow);
+        let js_navigator = editor::create_diff_navigator(editor.as_ref(), options);
+
+        let disposed = Rc::new(Cell::new(false));
+        let on_editor_disposed = editor.on_did_dispose({
+            let js_navigator: &JsValue = js_navigator.as_ref();
+            let js_navigator: IDiffNavigator = js_navigator.clone().unchecked_into();
+            let disposed = Rc::clone(&disposed);
+            move || {
+                if !disposed.replace(true) {
+                    js_navigator.dispose();
+                }
+            }
+        });
+        let js_editor: &JsValue = editor.as_ref().as_ref();
+        Self {
+            js_navigator,
+            js_editor: js_editor.clone().unchecked_into(),
+            disposed,
+            _on_editor_disposed: on_editor_disposed,
+        }
+    }
+
+    /// The diff editor this navigator moves through.
+    pub fn editor(&self) -> &IStandaloneDiffEditor {
+        &self.js_editor
+    }
+
+    /// Whether the diff editor has been disposed.
+    pub fn is_disposed(&self) -> bool {
+        self.disposed.get()
+    }
+
+    /// Whether there are changes to navigate to.
+    ///
+    /// Always `false` once the diff editor has been disposed.
+    pub fn can_navigate(&self) -> bool {
+        !self.is_disposed() && self.js_navigator.can_navigate()
+    }
+
+    /// Reveal the next change, wrapping around after the last one.
+    ///
+    /// Does nothing once the diff editor has been disposed.
+    pub fn next(&self) {
+        if !self.is_disposed() {
+            self.js_navigator.next();
+        }
+    }
+
+    /// Reveal the previous change, wrapping around before the first one.
+    ///
+    /// Does nothing once the diff editor has been disposed.
+    pub fn previous(&self) {
+        if !self.is_disposed() {
+            self.js_navigator.previous();
+        }
+    }
+}
+impl Drop for DiffNavigator {
+    fn drop(&mut self) {
+        if !self.disposed.replace(true) {
+            self.js_navigator.dispose();
+        }
+    }
+}
+
+impl AsRef<IDiffNavigator> for DiffNavigator {
+    fn as_ref(&self) -> &IDiffNavigator {
+        &self.js_navigator
+    }
+}
@@ -9,7 +9,10 @@ use crate::sys::{
         ICursorPositionChangedEvent,
         ICursorSelectionChangedEvent,
         IDimension,
+        IEditorMinimapOptions,
         IEditorMouseEvent,
+        IEditorOptions,
+        IEditorOptionsWordWrap,
         IModelChangedEvent,
         IModelContentChangedEvent,
         IModelLanguageChangedEvent,
@@ -21,8 +24,9 @@ use crate::sys::{
     IKeyboardEvent,
     IScrollEvent,
 };
+use js_sys::Object;
 use std::borrow::Borrow;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::HtmlElement;
 
 /// Switches to a theme.
@@ -122,6 +126,85 @@ impl From<CodeEditorOptions> for IStandaloneEditorConstructionOptions {
     }
 }
 
+/// Options that can be changed after the editor has been created.
+/// This represents a simplified version of [`IEditorOptions`].
+///
+/// Only the options that are set are changed when applied using
+/// [`CodeEditor::update_options`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EditorOptions {
+    pub read_only: Option<bool>,
+    pub font_family: Option<String>,
+    pub font_size: Option<f64>,
+    pub line_height: Option<f64>,
+    pub word_wrap: Option<IEditorOptionsWordWrap>,
+    /// Whether the minimap is shown.
+    pub minimap: Option<bool>,
+    pub folding: Option<bool>,
+    pub glyph_margin: Option<bool>,
+    pub scroll_beyond_last_line: Option<bool>,
+    pub automatic_layout: Option<bool>,
+}
+impl EditorOptions {
+    builder_methods! {
+        pub with read_only(bool);
+        pub with font_family(String);
+        pub with font_size(f64);
+        pub with line_height(f64);
+        pub with word_wrap(IEditorOptionsWordWrap);
+        pub with minimap(bool);
+        pub with folding(bool);
+        pub with glyph_margin(bool);
+        pub with scroll_beyond_last_line(bool);
+        pub with automatic_layout(bool);
+    }
+
+    /// Convert into [`IEditorOptions`].
+    pub fn to_sys_options(&self) -> IEditorOptions {
+        let options: IEditorOptions = Object::new().unchecked_into();
+
+        // this helps ensure we don't miss any members
+        let EditorOptions {
+            read_only,
+            font_family,
+            font_size,
+            line_height,
+            word_wrap,
+            minimap,
+            folding,
+            glyph_margin,
+            scroll_beyond_last_line,
+            automatic_layout,
+        } = self;
+
+        simple_setters! {
+            options =>
+                read_only,
+                ref font_family,
+                font_size,
+                line_height,
+                word_wrap,
+                folding,
+                glyph_margin,
+                scroll_beyond_last_line,
+                automatic_layout,
+        }
+        if let Some(enabled) = minimap {
+            let minimap = IEditorMinimapOptions::default();
+            minimap.set_enabled(Some(*enabled));
+            options.set_minimap(Some(&minimap));
+        }
+
+        options
+    }
+}
+
+impl From<&EditorOptions> for IEditorOptions {
+    fn from(options: &EditorOptions) -> Self {
+        options.to_sys_options()
+    }
+}
+
 /// Monaco code editor.
 ///
 /// This struct should be the sole owner of the underlying
@@ -237,6 +320,19 @@ impl CodeEditor {
         self.js_editor.set_model(None);
         model
     }
+
+    /// Update the editor's options after the editor has been created.
+    /// Options that aren't set keep their current value.
+    pub fn update_options(&self, options: &IEditorOptions) {
+        self.js_editor.update_options_editor(options);
+    }
+
+    /// Prevent the user from editing the text if `read_only` is `true`.
+    pub fn set_read_only(&self, read_only: bool) {
+        let options: IEditorOptions = Object::new().unchecked_into();
+        options.set_read_only(Some(read_only));
+        self.update_options(&options);
+    }
 }
 impl Drop for CodeEditor {
     fn drop(&mut self) {
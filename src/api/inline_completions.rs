@@ -0,0 +1,241 @@
+use super::{cancellation::cancellable, DisposableClosures, TextModel};
+use crate::sys::{
+    editor::ITextModel,
+    languages::{self, Command, CompletionItemKind, InlineCompletionTriggerKind},
+    CancellationToken,
+    IRange,
+    Position,
+};
+use js_sys::{Array, Object, Promise};
+use std::{cell::RefCell, convert::TryFrom, future::Future, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+/// Information about the suggestion that is currently selected in the
+/// suggest widget. This is the typed version of
+/// [`languages::SelectedSuggestionInfo`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SelectedSuggestionInfo {
+    pub range: IRange,
+    pub text: String,
+    pub is_snippet_text: bool,
+    pub completion_kind: CompletionItemKind,
+}
+impl From<&languages::SelectedSuggestionInfo> for SelectedSuggestionInfo {
+    fn from(info: &languages::SelectedSuggestionInfo) -> Self {
+        Self {
+            range: info.range(),
+            text: info.text(),
+            is_snippet_text: info.is_snippet_text(),
+            completion_kind: info.completion_kind(),
+        }
+    }
+}
+
+/// Context in which inline completions are requested.
+/// This is the typed version of [`languages::InlineCompletionContext`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InlineCompletionContext {
+    /// How the completion was triggered.
+    pub trigger_kind: InlineCompletionTriggerKind,
+    pub selected_suggestion_info: Option<SelectedSuggestionInfo>,
+}
+impl From<&languages::InlineCompletionContext> for InlineCompletionContext {
+    fn from(context: &languages::InlineCompletionContext) -> Self {
+        Self {
+            trigger_kind: context.trigger_kind(),
+            selected_suggestion_info: context
+                .selected_suggestion_info()
+                .as_ref()
+                .map(SelectedSuggestionInfo::from),
+        }
+    }
+}
+
+/// A single inline completion ("ghost text").
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InlineCompletion {
+    /// The text to insert.
+    /// If the text contains a line break, the range must end at the end of a
+    /// line. If existing text should be replaced, the existing text must be a
+    /// prefix of the text to insert.
+    pub text: String,
+    /// The range to replace.
+    /// Must begin and end on the same line.
+    pub range: Option<IRange>,
+    pub command: Option<Command>,
+    /// If set to `true`, unopened closing brackets are removed and unclosed
+    /// opening brackets are closed.
+    pub complete_bracket_pairs: Option<bool>,
+}
+impl InlineCompletion {
+    builder_methods! {
+        pub with range(IRange);
+        pub with command(Command);
+        pub with complete_bracket_pairs(bool);
+    }
+
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Convert into [`languages::InlineCompletion`].
+    pub fn to_sys_completion(&self) -> languages::InlineCompletion {
+        let completion: languages::InlineCompletion = Object::new().unchecked_into();
+        object_set!(completion.text = &self.text);
+        if let Some(range) = &self.range {
+            object_set!(completion.range = range);
+        }
+        if let Some(command) = &self.command {
+            object_set!(completion.command = command);
+        }
+        if let Some(complete_bracket_pairs) = self.complete_bracket_pairs {
+            object_set!(completion.completeBracketPairs = complete_bracket_pairs);
+        }
+        completion
+    }
+}
+
+impl From<&InlineCompletion> for languages::InlineCompletion {
+    fn from(completion: &InlineCompletion) -> Self {
+        completion.to_sys_completion()
+    }
+}
+
+/// Rust version of [`languages::InlineCompletionsProvider`].
+///
+/// Register it using [`register_inline_completions_provider`].
+pub trait InlineCompletionsProvider: 'static {
+    /// Provide the inline completions for the given `position`.
+    ///
+    /// The returned future is dropped when `token` requests cancellation,
+    /// the token is still passed along so it can be forwarded to other
+    /// cancellable APIs.
+    fn provide_inline_completions(
+        &self,
+        model: TextModel,
+        position: Position,
+        context: InlineCompletionContext,
+        token: CancellationToken,
+    ) -> impl Future<Output = Result<Vec<InlineCompletion>, JsValue>>;
+
+    /// Called when an item is shown.
+    fn handle_item_did_show(&self, _item: &InlineCompletion) {}
+
+    /// Called when a list of completions returned by
+    /// [`provide_inline_completions`](Self::provide_inline_completions) is
+    /// no longer in use.
+    fn free_inline_completions(&self, _completions: Vec<InlineCompletion>) {}
+}
+
+/// Completion lists that have been handed to Monaco but not freed yet.
+type LiveCompletions = Rc<RefCell<Vec<(languages::InlineCompletions, Vec<InlineCompletion>)>>>;
+
+type ProvideFn = dyn FnMut(
+    ITextModel,
+    Position,
+    languages::InlineCompletionContext,
+    CancellationToken,
+) -> Promise;
+type HandleItemDidShowFn = dyn FnMut(JsValue, JsValue);
+type FreeFn = dyn FnMut(JsValue);
+
+/// Register an inline completions provider for `language_id`.
+///
+/// The provider is unregistered when the returned value is dropped.
+pub fn register_inline_completions_provider(
+    language_id: &str,
+    provider: impl InlineCompletionsProvider,
+) -> DisposableClosures {
+    let provider = Rc::new(provider);
+    let live = LiveCompletions::default();
+
+    let provide = {
+        let provider = Rc::clone(&provider);
+        let live = Rc::clone(&live);
+        Closure::wrap(Box::new(
+            move |model: ITextModel,
+                  position: Position,
+                  context: languages::InlineCompletionContext,
+                  token: CancellationToken| {
+                let provider = Rc::clone(&provider);
+                let live = Rc::clone(&live);
+                let context = InlineCompletionContext::from(&context);
+                wasm_bindgen_futures::future_to_promise(async move {
+                    let future = provider.provide_inline_completions(
+                        TextModel::from(model),
+                        position,
+                        context,
+                        token.clone(),
+                    );
+                    let completions = match cancellable(&token, future).await {
+                        Some(completions) => completions?,
+                        None => return Ok(JsValue::UNDEFINED),
+                    };
+
+                    let items: Array = completions
+                        .iter()
+                        .map(InlineCompletion::to_sys_completion)
+                        .collect();
+                    let js_completions: languages::InlineCompletions =
+                        Object::new().unchecked_into();
+                    object_set!(js_completions.items = items);
+                    live.borrow_mut()
+                        .push((js_completions.clone(), completions));
+                    Ok(js_completions.into())
+                })
+            },
+        ) as Box<ProvideFn>)
+    };
+
+    let handle_item_did_show = {
+        let provider = Rc::clone(&provider);
+        let live = Rc::clone(&live);
+        Closure::wrap(Box::new(move |js_completions: JsValue, js_item: JsValue| {
+            let live = live.borrow();
+            let entry = live
+                .iter()
+                .find(|(candidate, _)| JsValue::from(candidate) == js_completions);
+            if let Some((candidate, completions)) = entry {
+                let index = candidate.items().index_of(&js_item, 0);
+                if let Some(item) = usize::try_from(index).ok().and_then(|i| completions.get(i)) {
+                    provider.handle_item_did_show(item);
+                }
+            }
+        }) as Box<HandleItemDidShowFn>)
+    };
+
+    let free = {
+        let provider = Rc::clone(&provider);
+        let live = Rc::clone(&live);
+        Closure::wrap(Box::new(move |js_completions: JsValue| {
+            let entry = {
+                let mut live = live.borrow_mut();
+                live.iter()
+                    .position(|(candidate, _)| JsValue::from(candidate) == js_completions)
+                    .map(|index| live.swap_remove(index))
+            };
+            if let Some((_, completions)) = entry {
+                provider.free_inline_completions(completions);
+            }
+        }) as Box<FreeFn>)
+    };
+
+    let js_provider: languages::InlineCompletionsProvider = Object::new().unchecked_into();
+    object_set!(js_provider.provideInlineCompletions = provide.as_ref());
+    object_set!(js_provider.handleItemDidShow = handle_item_did_show.as_ref());
+    object_set!(js_provider.freeInlineCompletions = free.as_ref());
+
+    let js_disposable = languages::register_inline_completions_provider(language_id, &js_provider);
+    DisposableClosures::new(
+        vec![
+            Box::new(provide),
+            Box::new(handle_item_did_show),
+            Box::new(free),
+            Box::new(live),
+        ],
+        js_disposable,
+    )
+}
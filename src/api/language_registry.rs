@@ -0,0 +1,407 @@
+use super::{
+    to_js_encoded_tokens_provider,
+    to_js_tokens_provider,
+    EncodedTokenizer,
+    Language,
+    LanguageConfiguration,
+    MonarchLanguage,
+    Tokenizer,
+};
+use crate::sys::{
+    languages::{self, TokensProviderFactory},
+    IDisposable,
+};
+use js_sys::{Function, Object, Promise};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// The tokenizer of a [`LoadedLanguage`].
+pub struct LanguageTokens {
+    provider: JsValue,
+    /// Whether `provider` is a Monarch definition.
+    monarch: bool,
+    closures: Vec<Box<dyn Any>>,
+}
+impl LanguageTokens {
+    /// Tokenize the language using a Monarch definition.
+    /// Fails if one of the regex attributes of `language` isn't a valid
+    /// regular expression.
+    pub fn monarch(language: &MonarchLanguage) -> Result<Self, JsValue> {
+        Ok(Self {
+            provider: language.to_sys_language()?.into(),
+            monarch: true,
+            closures: Vec::new(),
+        })
+    }
+
+    /// Tokenize the language using a Rust [`Tokenizer`].
+    pub fn tokenizer(tokenizer: impl Tokenizer) -> Self {
+        let (provider, closures) = to_js_tokens_provider(tokenizer);
+        Self {
+            provider: provider.into(),
+            monarch: false,
+            closures,
+        }
+    }
+
+    /// Tokenize the language using a Rust [`EncodedTokenizer`].
+    pub fn encoded_tokenizer(tokenizer: impl EncodedTokenizer) -> Self {
+        let (provider, closures) = to_js_encoded_tokens_provider(tokenizer);
+        Self {
+            provider: provider.into(),
+            monarch: false,
+            closures,
+        }
+    }
+}
+impl fmt::Debug for LanguageTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LanguageTokens")
+            .field("provider", &self.provider)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Everything a loader registered with [`LanguageRegistry::register`]
+/// provides for its language.
+#[derive(Debug, Default)]
+pub struct LoadedLanguage {
+    pub configuration: Option<LanguageConfiguration>,
+    pub tokens: Option<LanguageTokens>,
+}
+impl LoadedLanguage {
+    builder_methods! {
+        pub with configuration(LanguageConfiguration);
+        pub with tokens(LanguageTokens);
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The loading state of a language registered with a [`LanguageRegistry`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum LanguageLoadState {
+    /// The language hasn't been used yet.
+    NotLoaded,
+    /// The loader is running.
+    Loading,
+    /// The configuration and tokenizer of the language are in place.
+    Loaded,
+    /// The loader failed with the contained error.
+    Failed(JsValue),
+}
+impl LanguageLoadState {
+    pub fn is_loaded(&self) -> bool {
+        matches!(self, Self::Loaded)
+    }
+}
+
+type LoadFuture = Pin<Box<dyn Future<Output = Result<LoadedLanguage, JsValue>>>>;
+type Loader = Rc<dyn Fn() -> LoadFuture>;
+type Listener = Rc<dyn Fn(&str, &LanguageLoadState)>;
+
+struct Entry {
+    /// Distinguishes the entry from earlier registrations of the same
+    /// language, whose loads may still be running.
+    generation: usize,
+    state: LanguageLoadState,
+    loader: Loader,
+    /// Set while loading and after loading succeeded.
+    promise: Option<Promise>,
+    /// Whether Monaco's tokens provider factory waits for `promise`.
+    factory_waiting: bool,
+    /// Whether Monaco's tokens provider factory got a promise that failed.
+    /// Monaco doesn't ask again, so a later load has to set the tokens
+    /// provider itself.
+    factory_failed: bool,
+    closures: Vec<Box<dyn Any>>,
+    disposables: Vec<IDisposable>,
+}
+impl Drop for Entry {
+    fn drop(&mut self) {
+        for disposable in &self.disposables {
+            disposable.dispose();
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    listeners: Vec<(usize, Listener)>,
+    next_listener_id: usize,
+    next_generation: usize,
+}
+
+/// Registers languages whose configuration and tokenizer are only loaded when
+/// they're used for the first time.
+///
+/// Registering a language is cheap: only its [`Language`] extension point is
+/// passed to Monaco right away. The loader runs when Monaco first needs the
+/// language (for example because a model uses it) or when
+/// [`load`](Self::load) is called.
+///
+/// Cloning the registry returns a handle to the same registry.
+/// Configurations and tokenizers are disposed when the last handle is dropped,
+/// but the languages themselves stay registered as Monaco has no way to
+/// unregister them.
+#[derive(Clone, Default)]
+pub struct LanguageRegistry {
+    inner: Rc<RefCell<Inner>>,
+}
+impl LanguageRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `language` with a `loader` which is run on first use.
+    ///
+    /// Monaco only asks for a language once, so a loader that failed isn't
+    /// run again automatically. Call [`load`](Self::load) to retry it.
+    /// Registering a language that was already registered with this registry
+    /// replaces it. Loads of the previous registration that are still running
+    /// are ignored.
+    pub fn register<F>(&self, language: Language, loader: impl Fn() -> F + 'static)
+    where
+        F: Future<Output = Result<LoadedLanguage, JsValue>> + 'static,
+    {
+        language.register();
+        let id = language.id;
+
+        let create = {
+            let weak = Rc::downgrade(&self.inner);
+            let id = id.clone();
+            Closure::wrap(Box::new(move || match weak.upgrade() {
+                Some(inner) => {
+                    let promise = start_loading(&inner, &id);
+                    if let Some(entry) = inner.borrow_mut().entries.get_mut(&id) {
+                        entry.factory_waiting = true;
+                    }
+                    JsValue::from(promise)
+                }
+                None => JsValue::NULL,
+            }) as Box<dyn FnMut() -> JsValue>)
+        };
+        let factory: TokensProviderFactory = Object::new().unchecked_into();
+        object_set!(factory.create = create.as_ref());
+
+        let on_language = {
+            let weak = Rc::downgrade(&self.inner);
+            let id = id.clone();
+            Closure::wrap(Box::new(move || {
+                if let Some(inner) = weak.upgrade() {
+                    let _ = start_loading(&inner, &id);
+                }
+            }) as Box<dyn FnMut()>)
+        };
+
+        let disposables = vec![
+            languages::register_tokens_provider_factory(&id, &factory),
+            languages::on_language(&id, on_language.as_ref().unchecked_ref::<Function>()),
+        ];
+        let mut inner = self.inner.borrow_mut();
+        let generation = inner.next_generation;
+        inner.next_generation += 1;
+        let entry = Entry {
+            generation,
+            state: LanguageLoadState::NotLoaded,
+            loader: Rc::new(move || Box::pin(loader()) as LoadFuture),
+            promise: None,
+            factory_waiting: false,
+            factory_failed: false,
+            closures: vec![Box::new(create), Box::new(on_language)],
+            disposables,
+        };
+        let previous = inner.entries.insert(id.clone(), entry);
+        drop(inner);
+        // drop the previous entry outside of the borrow
+        drop(previous);
+        notify(&self.inner, &id);
+    }
+
+    /// Get the loading state of a language.
+    ///
+    /// Returns `None` if the language isn't registered with this registry.
+    pub fn load_state(&self, language_id: &str) -> Option<LanguageLoadState> {
+        self.inner
+            .borrow()
+            .entries
+            .get(language_id)
+            .map(|entry| entry.state.clone())
+    }
+
+    /// Load a language now instead of waiting for Monaco to need it.
+    ///
+    /// Resolves once the language is loaded. If it's already loaded or
+    /// loading, no additional work is done. If an earlier load failed, the
+    /// loader runs again.
+    pub async fn load(&self, language_id: &str) -> Result<(), JsValue> {
+        if !self.inner.borrow().entries.contains_key(language_id) {
+            return Err(js_sys::Error::new(&format!(
+                "language {:?} isn't registered with this registry",
+                language_id
+            ))
+            .into());
+        }
+        let promise = start_loading(&self.inner, language_id);
+        JsFuture::from(promise).await.map(|_| ())
+    }
+
+    /// Call `listener` whenever the loading state of a language changes.
+    ///
+    /// The listener is removed when the returned value is dropped.
+    pub fn on_did_change_load_state(
+        &self,
+        listener: impl Fn(&str, &LanguageLoadState) + 'static,
+    ) -> LoadStateListener {
+        let mut inner = self.inner.borrow_mut();
+        let id = inner.next_listener_id;
+        inner.next_listener_id += 1;
+        inner.listeners.push((id, Rc::new(listener)));
+        LoadStateListener {
+            registry: Rc::downgrade(&self.inner),
+            id,
+        }
+    }
+}
+impl fmt::Debug for LanguageRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+        let states: HashMap<_, _> = inner
+            .entries
+            .iter()
+            .map(|(id, entry)| (id, &entry.state))
+            .collect();
+        f.debug_struct("LanguageRegistry")
+            .field("languages", &states)
+            .finish()
+    }
+}
+
+/// Listener registered with [`LanguageRegistry::on_did_change_load_state`].
+#[must_use = "immediately removed when dropped"]
+#[derive(Debug)]
+pub struct LoadStateListener {
+    registry: Weak<RefCell<Inner>>,
+    id: usize,
+}
+impl Drop for LoadStateListener {
+    fn drop(&mut self) {
+        if let Some(inner) = self.registry.upgrade() {
+            inner
+                .borrow_mut()
+                .listeners
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+fn notify(inner: &Rc<RefCell<Inner>>, language_id: &str) {
+    let (state, listeners) = {
+        let inner = inner.borrow();
+        let state = match inner.entries.get(language_id) {
+            Some(entry) => entry.state.clone(),
+            None => return,
+        };
+        let listeners: Vec<_> = inner.listeners.iter().map(|(_, l)| Rc::clone(l)).collect();
+        (state, listeners)
+    };
+    for listener in listeners {
+        listener(language_id, &state);
+    }
+}
+
+/// Start loading the language unless it's already loading or loaded.
+///
+/// Returns a promise resolving to the tokens provider of the language, or
+/// `null` if it doesn't have one.
+fn start_loading(inner: &Rc<RefCell<Inner>>, language_id: &str) -> Promise {
+    let (loader, generation) = {
+        let inner = inner.borrow();
+        let entry = match inner.entries.get(language_id) {
+            Some(entry) => entry,
+            None => return Promise::resolve(&JsValue::NULL),
+        };
+        if let Some(promise) = &entry.promise {
+            return promise.clone();
+        }
+        (Rc::clone(&entry.loader), entry.generation)
+    };
+
+    let weak = Rc::downgrade(inner);
+    let id = language_id.to_owned();
+    let promise = wasm_bindgen_futures::future_to_promise(async move {
+        let result = loader().await;
+        let inner = match weak.upgrade() {
+            Some(inner) => inner,
+            None => return Ok(JsValue::NULL),
+        };
+
+        let output = {
+            let mut inner = inner.borrow_mut();
+            let entry = match inner.entries.get_mut(&id) {
+                // the language may have been registered again in the meantime
+                Some(entry) if entry.generation == generation => entry,
+                _ => return Ok(JsValue::NULL),
+            };
+            let result = result.and_then(|loaded| {
+                let disposable = loaded
+                    .configuration
+                    .as_ref()
+                    .map(|configuration| super::set_language_configuration(&id, configuration))
+                    .transpose()?;
+                Ok((disposable, loaded.tokens))
+            });
+            match result {
+                Ok((disposable, tokens)) => {
+                    entry.disposables.extend(disposable);
+                    entry.state = LanguageLoadState::Loaded;
+                    match tokens {
+                        Some(tokens) => {
+                            entry.closures.extend(tokens.closures);
+                            if entry.factory_failed && !entry.factory_waiting {
+                                entry.disposables.push(if tokens.monarch {
+                                    languages::set_monarch_tokens_provider(&id, &tokens.provider)
+                                } else {
+                                    languages::set_tokens_provider(&id, &tokens.provider)
+                                });
+                            }
+                            Ok(tokens.provider)
+                        }
+                        None => Ok(JsValue::NULL),
+                    }
+                }
+                Err(err) => {
+                    entry.state = LanguageLoadState::Failed(err.clone());
+                    // allow `load` to try again
+                    entry.promise = None;
+                    if entry.factory_waiting {
+                        entry.factory_waiting = false;
+                        entry.factory_failed = true;
+                    }
+                    Err(err)
+                }
+            }
+        };
+        notify(&inner, &id);
+        output
+    });
+
+    if let Some(entry) = inner.borrow_mut().entries.get_mut(language_id) {
+        entry.state = LanguageLoadState::Loading;
+        entry.promise = Some(promise.clone());
+    }
+    notify(inner, language_id);
+    promise
+}
@@ -0,0 +1,538 @@
+use crate::sys::{
+    languages::{self, ILanguageExtensionPoint, IndentAction},
+    IDisposable,
+};
+use js_sys::{Array, Object, RegExp};
+use std::convert::TryFrom;
+use wasm_bindgen::{prelude::*, JsCast};
+
+#[wasm_bindgen]
+extern "C" {
+    /// `RegExp(pattern)`, which throws a `SyntaxError` for invalid patterns.
+    #[wasm_bindgen(catch, js_name = "RegExp")]
+    fn new_regexp(pattern: &str) -> Result<RegExp, JsValue>;
+}
+
+fn to_js_array(values: &[String]) -> Array {
+    values.iter().map(JsValue::from).collect()
+}
+
+fn from_js_array(array: Option<Array>) -> Vec<String> {
+    array
+        .map(|array| array.iter().filter_map(|v| v.as_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Compile `pattern` into a [`RegExp`], returning the `SyntaxError` if it's
+/// invalid.
+pub(crate) fn to_js_regex(pattern: &str) -> Result<RegExp, JsValue> {
+    new_regexp(pattern)
+}
+
+/// Information about a language. This represents a simplified version of
+/// [`ILanguageExtensionPoint`].
+///
+/// Use [`register`](Self::register) to make the language known to Monaco.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Language {
+    pub id: String,
+    pub extensions: Vec<String>,
+    pub filenames: Vec<String>,
+    pub filename_patterns: Vec<String>,
+    /// Regular expression matched against the first line of a file.
+    pub first_line: Option<String>,
+    pub aliases: Vec<String>,
+    pub mimetypes: Vec<String>,
+}
+impl Language {
+    builder_methods! {
+        pub with first_line(String);
+    }
+
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Add a file extension (including the leading dot, e.g. `.rs`).
+    pub fn with_extension(mut self, extension: impl Into<String>) -> Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.filenames.push(filename.into());
+        self
+    }
+
+    pub fn with_filename_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.filename_patterns.push(pattern.into());
+        self
+    }
+
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    pub fn with_mimetype(mut self, mimetype: impl Into<String>) -> Self {
+        self.mimetypes.push(mimetype.into());
+        self
+    }
+
+    /// Register this language.
+    pub fn register(&self) {
+        languages::register(&self.to_sys_extension_point());
+    }
+
+    /// Register this language together with its editing configuration.
+    ///
+    /// Fails if one of the patterns in `configuration` isn't a valid regular
+    /// expression, in which case the language isn't registered either.
+    #[must_use = "the configuration is removed when the disposable is disposed"]
+    pub fn register_with_configuration(
+        &self,
+        configuration: &LanguageConfiguration,
+    ) -> Result<IDisposable, JsValue> {
+        let configuration = configuration.to_sys_configuration()?;
+        self.register();
+        Ok(languages::set_language_configuration(
+            &self.id,
+            &configuration,
+        ))
+    }
+
+    /// Convert into [`ILanguageExtensionPoint`].
+    pub fn to_sys_extension_point(&self) -> ILanguageExtensionPoint {
+        let point: ILanguageExtensionPoint = Object::new().unchecked_into();
+        point.set_id(&self.id);
+
+        let set_list = |values: &[String], setter: fn(&ILanguageExtensionPoint, Option<&Array>)| {
+            if !values.is_empty() {
+                setter(&point, Some(&to_js_array(values)));
+            }
+        };
+        set_list(&self.extensions, ILanguageExtensionPoint::set_extensions);
+        set_list(&self.filenames, ILanguageExtensionPoint::set_filenames);
+        set_list(
+            &self.filename_patterns,
+            ILanguageExtensionPoint::set_filename_patterns,
+        );
+        set_list(&self.aliases, ILanguageExtensionPoint::set_aliases);
+        set_list(&self.mimetypes, ILanguageExtensionPoint::set_mimetypes);
+
+        point.set_first_line(self.first_line.as_deref());
+        point
+    }
+}
+
+impl From<&ILanguageExtensionPoint> for Language {
+    fn from(point: &ILanguageExtensionPoint) -> Self {
+        Self {
+            id: point.id(),
+            extensions: from_js_array(point.extensions()),
+            filenames: from_js_array(point.filenames()),
+            filename_patterns: from_js_array(point.filename_patterns()),
+            first_line: point.first_line(),
+            aliases: from_js_array(point.aliases()),
+            mimetypes: from_js_array(point.mimetypes()),
+        }
+    }
+}
+
+impl From<&Language> for ILanguageExtensionPoint {
+    fn from(language: &Language) -> Self {
+        language.to_sys_extension_point()
+    }
+}
+
+/// Get all the registered languages.
+pub fn languages() -> Vec<Language> {
+    languages::get_languages()
+        .iter()
+        .map(JsCast::unchecked_into::<ILanguageExtensionPoint>)
+        .map(|point| Language::from(&point))
+        .collect()
+}
+
+/// Set the editing configuration for a language.
+///
+/// Fails if one of the patterns in `configuration` isn't a valid regular
+/// expression.
+/// Disposing the returned [`IDisposable`] removes the configuration again.
+#[must_use = "the configuration is removed when the disposable is disposed"]
+pub fn set_language_configuration(
+    language_id: &str,
+    configuration: &LanguageConfiguration,
+) -> Result<IDisposable, JsValue> {
+    let configuration = configuration.to_sys_configuration()?;
+    Ok(languages::set_language_configuration(
+        language_id,
+        &configuration,
+    ))
+}
+
+/// A pair of opening and closing characters, like `("{", "}")`.
+pub type CharacterPair = (String, String);
+
+fn pair_to_js_array((open, close): &CharacterPair) -> Array {
+    Array::of2(&JsValue::from(open), &JsValue::from(close))
+}
+
+fn pairs_to_js_array(pairs: &[CharacterPair]) -> Array {
+    pairs.iter().map(pair_to_js_array).collect()
+}
+
+/// Describes how comments for a language work.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommentRule {
+    /// The line comment token, like `// this is a comment`
+    pub line_comment: Option<String>,
+    /// The block comment character pair, like `/* block comment */`
+    pub block_comment: Option<CharacterPair>,
+}
+impl CommentRule {
+    builder_methods! {
+        pub with line_comment(String);
+        pub with block_comment(CharacterPair);
+    }
+
+    /// Convert into [`languages::CommentRule`].
+    pub fn to_sys_rule(&self) -> languages::CommentRule {
+        let rule: languages::CommentRule = Object::new().unchecked_into();
+        rule.set_line_comment(self.line_comment.as_deref());
+        if let Some(pair) = &self.block_comment {
+            rule.set_block_comment(Some(&pair_to_js_array(pair)));
+        }
+        rule
+    }
+}
+
+/// A pair of characters that is closed automatically when the opening
+/// character is typed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AutoClosingPair {
+    pub open: String,
+    pub close: String,
+    /// Scopes in which the pair isn't closed (`"string"` or `"comment"`).
+    pub not_in: Vec<String>,
+}
+impl AutoClosingPair {
+    pub fn new(open: impl Into<String>, close: impl Into<String>) -> Self {
+        Self {
+            open: open.into(),
+            close: close.into(),
+            not_in: Vec::new(),
+        }
+    }
+
+    pub fn with_not_in(mut self, scope: impl Into<String>) -> Self {
+        self.not_in.push(scope.into());
+        self
+    }
+
+    /// Convert into [`languages::IAutoClosingPairConditional`].
+    pub fn to_sys_pair(&self) -> languages::IAutoClosingPairConditional {
+        let pair: languages::IAutoClosingPairConditional = Object::new().unchecked_into();
+        let base: &languages::IAutoClosingPair = pair.as_ref();
+        base.set_open(&self.open);
+        base.set_close(&self.close);
+        if !self.not_in.is_empty() {
+            pair.set_not_in(Some(&to_js_array(&self.not_in)));
+        }
+        pair
+    }
+}
+
+/// Describes indentation rules for a language.
+/// The patterns are regular expressions.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IndentationRule {
+    /// If a line matches this pattern, then all the lines after it should be
+    /// unindented once (until another rule matches).
+    pub decrease_indent_pattern: String,
+    /// If a line matches this pattern, then all the lines after it should be
+    /// indented once (until another rule matches).
+    pub increase_indent_pattern: String,
+    /// If a line matches this pattern, then **only the next line** after it
+    /// should be indented once.
+    pub indent_next_line_pattern: Option<String>,
+    /// If a line matches this pattern, then its indentation should not be
+    /// changed and it should not be evaluated against the other rules.
+    pub un_indented_line_pattern: Option<String>,
+}
+impl IndentationRule {
+    builder_methods! {
+        pub with indent_next_line_pattern(String);
+        pub with un_indented_line_pattern(String);
+    }
+
+    pub fn new(
+        increase_indent_pattern: impl Into<String>,
+        decrease_indent_pattern: impl Into<String>,
+    ) -> Self {
+        Self {
+            increase_indent_pattern: increase_indent_pattern.into(),
+            decrease_indent_pattern: decrease_indent_pattern.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Convert into [`languages::IndentationRule`].
+    /// Fails if one of the patterns isn't a valid regular expression.
+    pub fn to_sys_rule(&self) -> Result<languages::IndentationRule, JsValue> {
+        let rule: languages::IndentationRule = Object::new().unchecked_into();
+        rule.set_decrease_indent_pattern(&to_js_regex(&self.decrease_indent_pattern)?);
+        rule.set_increase_indent_pattern(&to_js_regex(&self.increase_indent_pattern)?);
+        if let Some(pattern) = &self.indent_next_line_pattern {
+            rule.set_indent_next_line_pattern(Some(&to_js_regex(pattern)?));
+        }
+        if let Some(pattern) = &self.un_indented_line_pattern {
+            rule.set_un_indented_line_pattern(Some(&to_js_regex(pattern)?));
+        }
+        Ok(rule)
+    }
+}
+
+/// Describes language specific folding markers such as '#region' and
+/// '#endregion'. The patterns should start with '^'.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FoldingMarkers {
+    pub start: String,
+    pub end: String,
+}
+impl FoldingMarkers {
+    pub fn new(start: impl Into<String>, end: impl Into<String>) -> Self {
+        Self {
+            start: start.into(),
+            end: end.into(),
+        }
+    }
+
+    /// Convert into [`languages::FoldingMarkers`].
+    /// Fails if one of the patterns isn't a valid regular expression.
+    pub fn to_sys_markers(&self) -> Result<languages::FoldingMarkers, JsValue> {
+        let markers: languages::FoldingMarkers = Object::new().unchecked_into();
+        markers.set_start(&to_js_regex(&self.start)?);
+        markers.set_end(&to_js_regex(&self.end)?);
+        Ok(markers)
+    }
+}
+
+/// Describes folding rules for a language.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FoldingRules {
+    /// Whether the language adheres to the off-side rule, i.e. blocks are
+    /// expressed by their indentation.
+    pub off_side: Option<bool>,
+    /// Region markers used by the language.
+    pub markers: Option<FoldingMarkers>,
+}
+impl FoldingRules {
+    builder_methods! {
+        pub with off_side(bool);
+        pub with markers(FoldingMarkers);
+    }
+
+    /// Convert into [`languages::FoldingRules`].
+    /// Fails if one of the marker patterns isn't a valid regular expression.
+    pub fn to_sys_rules(&self) -> Result<languages::FoldingRules, JsValue> {
+        let rules: languages::FoldingRules = Object::new().unchecked_into();
+        rules.set_off_side(self.off_side);
+        if let Some(markers) = &self.markers {
+            rules.set_markers(Some(&markers.to_sys_markers()?));
+        }
+        Ok(rules)
+    }
+}
+
+/// Describes what to do when pressing Enter.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnterAction {
+    /// Describe what to do with the indentation.
+    pub indent_action: IndentAction,
+    /// Describes text to be appended after the new line and after the
+    /// indentation.
+    pub append_text: Option<String>,
+    /// Describes the number of characters to remove from the new line's
+    /// indentation.
+    pub remove_text: Option<u32>,
+}
+impl EnterAction {
+    builder_methods! {
+        pub with append_text(String);
+        pub with remove_text(u32);
+    }
+
+    pub fn new(indent_action: IndentAction) -> Self {
+        Self {
+            indent_action,
+            append_text: None,
+            remove_text: None,
+        }
+    }
+
+    /// Convert into [`languages::EnterAction`].
+    pub fn to_sys_action(&self) -> languages::EnterAction {
+        let action: languages::EnterAction = Object::new().unchecked_into();
+        action.set_indent_action(self.indent_action);
+        action.set_append_text(self.append_text.as_deref());
+        action.set_remove_text(self.remove_text.map(f64::from));
+        action
+    }
+}
+
+/// Describes a rule to be evaluated when pressing Enter.
+/// The patterns are regular expressions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OnEnterRule {
+    /// This rule will only execute if the text before the cursor matches this
+    /// pattern.
+    pub before_text: String,
+    /// This rule will only execute if the text after the cursor matches this
+    /// pattern.
+    pub after_text: Option<String>,
+    /// This rule will only execute if the text above the this line matches
+    /// this pattern.
+    pub previous_line_text: Option<String>,
+    /// The action to execute.
+    pub action: EnterAction,
+}
+impl OnEnterRule {
+    builder_methods! {
+        pub with after_text(String);
+        pub with previous_line_text(String);
+    }
+
+    pub fn new(before_text: impl Into<String>, action: EnterAction) -> Self {
+        Self {
+            before_text: before_text.into(),
+            after_text: None,
+            previous_line_text: None,
+            action,
+        }
+    }
+
+    /// Convert into [`languages::OnEnterRule`].
+    /// Fails if one of the patterns isn't a valid regular expression.
+    pub fn to_sys_rule(&self) -> Result<languages::OnEnterRule, JsValue> {
+        let rule: languages::OnEnterRule = Object::new().unchecked_into();
+        rule.set_before_text(&to_js_regex(&self.before_text)?);
+        if let Some(pattern) = &self.after_text {
+            rule.set_after_text(Some(&to_js_regex(pattern)?));
+        }
+        if let Some(pattern) = &self.previous_line_text {
+            rule.set_previous_line_text(Some(&to_js_regex(pattern)?));
+        }
+        rule.set_action(&self.action.to_sys_action());
+        Ok(rule)
+    }
+}
+
+/// Editing configuration of a language. This represents a simplified version
+/// of [`languages::LanguageConfiguration`].
+///
+/// Fields that are `None` use Monaco's defaults.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LanguageConfiguration {
+    pub comments: Option<CommentRule>,
+    pub brackets: Option<Vec<CharacterPair>>,
+    /// The language's word definition as a regular expression.
+    pub word_pattern: Option<String>,
+    pub indentation_rules: Option<IndentationRule>,
+    pub on_enter_rules: Option<Vec<OnEnterRule>>,
+    /// If not set, the configured brackets will be used.
+    pub auto_closing_pairs: Option<Vec<AutoClosingPair>>,
+    /// If not set, the auto closing pairs will be used.
+    pub surrounding_pairs: Option<Vec<CharacterPair>>,
+    /// If not set, the configured brackets will be used.
+    pub colorized_bracket_pairs: Option<Vec<CharacterPair>>,
+    /// Characters that must be after the cursor for bracket or quote
+    /// autoclosing to occur.
+    pub auto_close_before: Option<String>,
+    pub folding: Option<FoldingRules>,
+}
+impl LanguageConfiguration {
+    builder_methods! {
+        pub with comments(CommentRule);
+        pub with brackets(Vec<CharacterPair>);
+        pub with word_pattern(String);
+        pub with indentation_rules(IndentationRule);
+        pub with on_enter_rules(Vec<OnEnterRule>);
+        pub with auto_closing_pairs(Vec<AutoClosingPair>);
+        pub with surrounding_pairs(Vec<CharacterPair>);
+        pub with colorized_bracket_pairs(Vec<CharacterPair>);
+        pub with auto_close_before(String);
+        pub with folding(FoldingRules);
+    }
+
+    /// Convert into [`languages::LanguageConfiguration`].
+    /// Fails if one of the patterns isn't a valid regular expression.
+    pub fn to_sys_configuration(&self) -> Result<languages::LanguageConfiguration, JsValue> {
+        let config: languages::LanguageConfiguration = Object::new().unchecked_into();
+
+        // this helps ensure we don't miss any members
+        let LanguageConfiguration {
+            comments,
+            brackets,
+            word_pattern,
+            indentation_rules,
+            on_enter_rules,
+            auto_closing_pairs,
+            surrounding_pairs,
+            colorized_bracket_pairs,
+            auto_close_before,
+            folding,
+        } = self;
+
+        if let Some(comments) = comments {
+            config.set_comments(Some(&comments.to_sys_rule()));
+        }
+        if let Some(brackets) = brackets {
+            config.set_brackets(Some(&pairs_to_js_array(brackets)));
+        }
+        if let Some(pattern) = word_pattern {
+            config.set_word_pattern(Some(&to_js_regex(pattern)?));
+        }
+        if let Some(rules) = indentation_rules {
+            config.set_indentation_rules(Some(&rules.to_sys_rule()?));
+        }
+        if let Some(rules) = on_enter_rules {
+            let rules = rules
+                .iter()
+                .map(OnEnterRule::to_sys_rule)
+                .collect::<Result<Array, _>>()?;
+            config.set_on_enter_rules(Some(&rules));
+        }
+        if let Some(pairs) = auto_closing_pairs {
+            let pairs: Array = pairs.iter().map(AutoClosingPair::to_sys_pair).collect();
+            config.set_auto_closing_pairs(Some(&pairs));
+        }
+        if let Some(pairs) = surrounding_pairs {
+            let pairs: Array = pairs
+                .iter()
+                .map(|(open, close)| AutoClosingPair::new(open, close).to_sys_pair())
+                .collect();
+            config.set_surrounding_pairs(Some(&pairs));
+        }
+        if let Some(pairs) = colorized_bracket_pairs {
+            config.set_colorized_bracket_pairs(Some(&pairs_to_js_array(pairs)));
+        }
+        config.set_auto_close_before(auto_close_before.as_deref());
+        if let Some(folding) = folding {
+            config.set_folding(Some(&folding.to_sys_rules()?));
+        }
+
+        Ok(config)
+    }
+}
+
+impl TryFrom<&LanguageConfiguration> for languages::LanguageConfiguration {
+    type Error = JsValue;
+
+    fn try_from(configuration: &LanguageConfiguration) -> Result<Self, Self::Error> {
+        configuration.to_sys_configuration()
+    }
+}
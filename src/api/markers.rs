@@ -0,0 +1,88 @@
+use super::TextModel;
+use crate::sys::{
+    editor::{self, IMarkerData},
+    MarkerSeverity,
+};
+use js_sys::{Array, Object};
+use wasm_bindgen::JsCast;
+
+/// A diagnostic, like an error or a warning, shown in the editor.
+/// This is the typed version of [`IMarkerData`].
+///
+/// Set them using [`TextModel::set_markers`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Marker {
+    pub severity: MarkerSeverity,
+    pub message: String,
+    /// Where the marker comes from, for example the name of a linter.
+    pub source: Option<String>,
+    pub start_line_number: u32,
+    pub start_column: u32,
+    pub end_line_number: u32,
+    pub end_column: u32,
+}
+impl Marker {
+    builder_methods! {
+        pub with source(String);
+    }
+
+    pub fn new(
+        severity: MarkerSeverity,
+        message: impl Into<String>,
+        start_line_number: u32,
+        start_column: u32,
+        end_line_number: u32,
+        end_column: u32,
+    ) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            source: None,
+            start_line_number,
+            start_column,
+            end_line_number,
+            end_column,
+        }
+    }
+
+    /// Convert into [`IMarkerData`].
+    pub fn to_sys_marker(&self) -> IMarkerData {
+        // this helps ensure we don't miss any members
+        let Self {
+            severity,
+            message,
+            source,
+            start_line_number,
+            start_column,
+            end_line_number,
+            end_column,
+        } = self;
+
+        let marker: IMarkerData = Object::new().unchecked_into();
+        marker.set_severity(*severity);
+        marker.set_message(message);
+        marker.set_source(source.as_deref());
+        marker.set_start_line_number((*start_line_number).into());
+        marker.set_start_column((*start_column).into());
+        marker.set_end_line_number((*end_line_number).into());
+        marker.set_end_column((*end_column).into());
+        marker
+    }
+}
+
+impl From<&Marker> for IMarkerData {
+    fn from(marker: &Marker) -> Self {
+        marker.to_sys_marker()
+    }
+}
+
+impl TextModel {
+    /// Replace the markers of `owner` on this model.
+    ///
+    /// Every owner has its own set of markers, so multiple sources of
+    /// diagnostics don't overwrite each other.
+    pub fn set_markers(&self, owner: &str, markers: &[Marker]) {
+        let markers = markers.iter().map(Marker::to_sys_marker).collect::<Array>();
+        editor::set_model_markers(self.as_ref(), owner, &markers);
+    }
+}
@@ -4,19 +4,54 @@
 //! Most of the types here are simply wrappers around the Javascript types and
 //! implement [`AsRef`] for them.
 use crate::sys::IDisposable;
+pub use colorize::*;
+pub use decorations::*;
+pub use diff_editor::*;
 pub use editor::*;
+pub use inline_completions::*;
+pub use language_registry::*;
+pub use languages::*;
+pub use markers::*;
 pub use model::*;
+pub use monarch::*;
+use std::{any::Any, fmt};
+#[cfg(feature = "textmate")]
+pub use textmate::*;
+pub use theme::*;
+pub use theme_registry::*;
+pub use tokens::*;
+pub use view_zones::*;
+#[cfg(feature = "vscode-themes")]
+pub use vscode_theme::*;
 use wasm_bindgen::closure::Closure;
+pub use widgets::*;
 
 #[macro_use]
 mod macros;
 
+mod cancellation;
+mod colorize;
+mod decorations;
+mod diff_editor;
 mod editor;
+mod inline_completions;
+mod language_registry;
+mod languages;
+mod markers;
 mod model;
+mod monarch;
+#[cfg(feature = "textmate")]
+mod textmate;
+mod theme;
+mod theme_registry;
+mod tokens;
+mod view_zones;
+#[cfg(feature = "vscode-themes")]
+mod vscode_theme;
+mod widgets;
 
 /// A [`Closure`] that is tied to an [`IDisposable`].
 #[must_use = "immediately disposed when dropped"]
-#[derive(Debug)]
 pub struct DisposableClosure<T: ?Sized> {
     _closure: Closure<T>,
     js_disposable: IDisposable,
@@ -35,8 +70,53 @@ impl<T: ?Sized> Drop for DisposableClosure<T> {
     }
 }
 
+impl<T: ?Sized> fmt::Debug for DisposableClosure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DisposableClosure")
+            .field("js_disposable", &self.js_disposable)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<T> AsRef<IDisposable> for DisposableClosure<T> {
     fn as_ref(&self) -> &IDisposable {
         &self.js_disposable
     }
 }
+
+/// A group of [`Closure`]s (and any state they share) that is tied to a
+/// single [`IDisposable`].
+///
+/// This is returned when registering Rust implementations of Monaco's
+/// provider interfaces which consist of multiple callbacks.
+#[must_use = "immediately disposed when dropped"]
+pub struct DisposableClosures {
+    _closures: Vec<Box<dyn Any>>,
+    js_disposable: IDisposable,
+}
+impl DisposableClosures {
+    pub fn new(closures: Vec<Box<dyn Any>>, js_disposable: IDisposable) -> Self {
+        Self {
+            _closures: closures,
+            js_disposable,
+        }
+    }
+}
+impl Drop for DisposableClosures {
+    fn drop(&mut self) {
+        self.js_disposable.dispose();
+    }
+}
+impl fmt::Debug for DisposableClosures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DisposableClosures")
+            .field("js_disposable", &self.js_disposable)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<IDisposable> for DisposableClosures {
+    fn as_ref(&self) -> &IDisposable {
+        &self.js_disposable
+    }
+}
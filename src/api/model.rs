@@ -1,13 +1,17 @@
 use crate::sys::{
     editor::{
         self,
+        IIdentifiedSingleEditOperation,
         IModelContentChangedEvent,
         IModelLanguageChangedEvent,
         IModelOptionsChangedEvent,
         ITextModel,
     },
+    Range,
     Uri,
 };
+use js_sys::{Array, Object};
+use std::borrow::Cow;
 use wasm_bindgen::{JsCast, JsValue};
 
 /// Models are a more abstract representation of files that can be "opened"
@@ -96,6 +100,86 @@ impl TextModel {
     pub fn set_value(&self, value: &str) {
         self.js_model.set_value(value)
     }
+
+    /// Change the text to `value` by only replacing the part that differs.
+    ///
+    /// Unlike [`set_value`](Self::set_value) the edit lands on the undo stack
+    /// and cursors outside of the changed part stay where they are.
+    /// Nothing happens if the text is already equal to `value`.
+    /// Line breaks in `value` are converted to the model's end of line
+    /// sequence first, so `value` may use either.
+    pub fn apply_value(&self, value: &str) {
+        let value = normalize_eol(value, &self.js_model.get_eol());
+        let old: Vec<u16> = self.get_value().encode_utf16().collect();
+        let new: Vec<u16> = value.encode_utf16().collect();
+        if old == new {
+            return;
+        }
+
+        // Monaco offsets are in UTF-16 code units
+        let mut prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        // don't split a surrogate pair
+        if prefix > 0 && is_high_surrogate(old[prefix - 1]) {
+            prefix -= 1;
+        }
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let mut suffix = old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if suffix > 0 && is_low_surrogate(old[old.len() - suffix]) {
+            suffix -= 1;
+        }
+
+        let start = self.js_model.get_position_at(prefix as f64);
+        let end = self.js_model.get_position_at((old.len() - suffix) as f64);
+        let text = String::from_utf16_lossy(&new[prefix..new.len() - suffix]);
+
+        let operation: IIdentifiedSingleEditOperation = Object::new().unchecked_into();
+        operation.set_range(&Range::new(
+            start.line_number(),
+            start.column(),
+            end.line_number(),
+            end.column(),
+        ));
+        operation.set_text(Some(&text));
+
+        self.js_model.push_stack_element();
+        self.js_model
+            .push_edit_operations(&Array::new(), &Array::of1(&operation), None);
+        self.js_model.push_stack_element();
+    }
+}
+
+/// Replace every line break (`\r\n`, `\r` or `\n`) in `value` with `eol`.
+fn normalize_eol<'a>(value: &'a str, eol: &str) -> Cow<'a, str> {
+    if eol == "\n" && !value.contains('\r') {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                out.push_str(eol);
+            }
+            '\n' => out.push_str(eol),
+            _ => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+fn is_high_surrogate(unit: u16) -> bool {
+    (0xD800..0xDC00).contains(&unit)
+}
+
+fn is_low_surrogate(unit: u16) -> bool {
+    (0xDC00..0xE000).contains(&unit)
 }
 
 impl AsRef<ITextModel> for TextModel {
@@ -108,3 +192,19 @@ impl From<ITextModel> for TextModel {
         Self { js_model }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_eol_to_lf() {
+        assert_eq!(normalize_eol("a\nb", "\n"), "a\nb");
+        assert_eq!(normalize_eol("a\r\nb\rc\n", "\n"), "a\nb\nc\n");
+    }
+
+    #[test]
+    fn normalize_eol_to_crlf() {
+        assert_eq!(normalize_eol("a\nb\r\nc\r", "\r\n"), "a\r\nb\r\nc\r\n");
+    }
+}
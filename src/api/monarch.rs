@@ -0,0 +1,812 @@
+use super::to_js_regex;
+use crate::sys::{
+    languages::{
+        self,
+        IExpandedMonarchLanguageAction,
+        IExpandedMonarchLanguageRule,
+        IMonarchLanguage,
+        IMonarchLanguageBracket,
+    },
+    IDisposable,
+};
+use js_sys::{Array, Object, Reflect};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, convert::TryFrom};
+use wasm_bindgen::{JsCast, JsValue};
+
+fn set_key(target: &Object, key: &str, value: &JsValue) {
+    Reflect::set(target, &JsValue::from_str(key), value)
+        .expect("setting key on object must not fail");
+}
+
+/// Serde helpers for maps whose order is significant and which are therefore
+/// stored as a list of entries.
+#[cfg(feature = "serde")]
+mod ordered_map {
+    use serde::{
+        de::{MapAccess, Visitor},
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    };
+    use std::{fmt, marker::PhantomData};
+
+    pub fn serialize<S, T>(entries: &[(String, T)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        serializer.collect_map(entries.iter().map(|(k, v)| (k, v)))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<(String, T)>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        struct EntriesVisitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for EntriesVisitor<T> {
+            type Value = Vec<(String, T)>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or_default());
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(entries)
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor(PhantomData))
+    }
+}
+
+/// Bracket action of a [`MonarchAction`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MonarchBracketAction {
+    #[cfg_attr(feature = "serde", serde(rename = "@open"))]
+    Open,
+    #[cfg_attr(feature = "serde", serde(rename = "@close"))]
+    Close,
+}
+impl MonarchBracketAction {
+    /// Get the value used by Monarch.
+    pub fn to_value(&self) -> &'static str {
+        match self {
+            Self::Open => "@open",
+            Self::Close => "@close",
+        }
+    }
+}
+
+/// Expanded form of a [`MonarchAction`].
+/// This is the typed version of [`IExpandedMonarchLanguageAction`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "camelCase", deny_unknown_fields)
+)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExpandedMonarchAction {
+    /// Token class (ie. css class) (or "@brackets" or "@rematch").
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub token: Option<String>,
+    /// The next state to push, or "@push", "@pop", "@popall".
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub next: Option<String>,
+    /// Switch to this state.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub switch_to: Option<String>,
+    /// Go back n characters in the stream.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub go_back: Option<u32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub bracket: Option<MonarchBracketAction>,
+    /// Switch to embedded language (using the mimetype) or get out using
+    /// "@pop".
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub next_embedded: Option<String>,
+    /// Log a message to the browser console window.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub log: Option<String>,
+    /// Map from guard to action. The first matching guard is used.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty", with = "ordered_map")
+    )]
+    pub cases: Vec<(String, MonarchAction)>,
+}
+impl ExpandedMonarchAction {
+    /// Create an action that assigns a token class.
+    ///
+    /// Use the `with_` methods to set the other members.
+    pub fn token(token: impl Into<String>) -> Self {
+        Self {
+            token: Some(token.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Set the next state to push, or "@push", "@pop", "@popall".
+    pub fn with_next(mut self, next: impl Into<String>) -> Self {
+        self.next = Some(next.into());
+        self
+    }
+
+    pub fn with_switch_to(mut self, state: impl Into<String>) -> Self {
+        self.switch_to = Some(state.into());
+        self
+    }
+
+    pub fn with_go_back(mut self, n: u32) -> Self {
+        self.go_back = Some(n);
+        self
+    }
+
+    pub fn with_bracket(mut self, bracket: MonarchBracketAction) -> Self {
+        self.bracket = Some(bracket);
+        self
+    }
+
+    pub fn with_next_embedded(mut self, language: impl Into<String>) -> Self {
+        self.next_embedded = Some(language.into());
+        self
+    }
+
+    pub fn with_log(mut self, message: impl Into<String>) -> Self {
+        self.log = Some(message.into());
+        self
+    }
+
+    /// Convert into [`IExpandedMonarchLanguageAction`].
+    pub fn to_sys_action(&self) -> IExpandedMonarchLanguageAction {
+        let action: IExpandedMonarchLanguageAction = Object::new().unchecked_into();
+
+        // this helps ensure we don't miss any members
+        let ExpandedMonarchAction {
+            token,
+            next,
+            switch_to,
+            go_back,
+            bracket,
+            next_embedded,
+            log,
+            cases,
+        } = self;
+
+        action.set_token(token.as_deref());
+        action.set_next(next.as_deref());
+        action.set_switch_to(switch_to.as_deref());
+        action.set_go_back(go_back.map(f64::from));
+        action.set_bracket(bracket.as_ref().map(MonarchBracketAction::to_value));
+        action.set_next_embedded(next_embedded.as_deref());
+        action.set_log(log.as_deref());
+        if !cases.is_empty() {
+            let js_cases = Object::new();
+            for (guard, case) in cases {
+                set_key(&js_cases, guard, &case.to_js_value());
+            }
+            action.set_cases(Some(&js_cases));
+        }
+        action
+    }
+}
+
+/// Action to take when a [`MonarchRule`] matches.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MonarchAction {
+    /// Assign a token class.
+    Token(String),
+    /// One action for each parenthesized match group.
+    Group(Vec<MonarchAction>),
+    Expanded(Box<ExpandedMonarchAction>),
+}
+impl MonarchAction {
+    pub fn token(token: impl Into<String>) -> Self {
+        Self::Token(token.into())
+    }
+
+    pub fn group(actions: Vec<MonarchAction>) -> Self {
+        Self::Group(actions)
+    }
+
+    /// Create an action that selects one of `cases` based on the matched
+    /// text.
+    pub fn cases<K, V>(cases: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<MonarchAction>,
+    {
+        Self::Expanded(Box::new(ExpandedMonarchAction {
+            cases: cases
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+            ..ExpandedMonarchAction::default()
+        }))
+    }
+
+    /// Convert to the Javascript value Monarch expects.
+    pub fn to_js_value(&self) -> JsValue {
+        match self {
+            Self::Token(token) => JsValue::from_str(token),
+            Self::Group(actions) => actions
+                .iter()
+                .map(MonarchAction::to_js_value)
+                .collect::<Array>()
+                .into(),
+            Self::Expanded(action) => action.to_sys_action().into(),
+        }
+    }
+}
+
+impl From<&str> for MonarchAction {
+    fn from(token: &str) -> Self {
+        Self::token(token)
+    }
+}
+impl From<String> for MonarchAction {
+    fn from(token: String) -> Self {
+        Self::Token(token)
+    }
+}
+impl From<ExpandedMonarchAction> for MonarchAction {
+    fn from(action: ExpandedMonarchAction) -> Self {
+        Self::Expanded(Box::new(action))
+    }
+}
+
+/// A rule of a Monarch tokenizer state.
+/// This is the typed version of [`IExpandedMonarchLanguageRule`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged, try_from = "MonarchRuleRepr"))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MonarchRule {
+    /// Include all rules from another state.
+    Include { include: String },
+    /// Apply `action` when `regex` matches.
+    ///
+    /// Attributes can be referenced with `@name`.
+    Match {
+        regex: String,
+        action: MonarchAction,
+    },
+}
+impl MonarchRule {
+    pub fn new(regex: impl Into<String>, action: impl Into<MonarchAction>) -> Self {
+        Self::Match {
+            regex: regex.into(),
+            action: action.into(),
+        }
+    }
+
+    /// Include the rules of `state` (e.g. `"@whitespace"`).
+    pub fn include(state: impl Into<String>) -> Self {
+        Self::Include {
+            include: state.into(),
+        }
+    }
+
+    /// Convert into [`IExpandedMonarchLanguageRule`].
+    pub fn to_sys_rule(&self) -> IExpandedMonarchLanguageRule {
+        let rule: IExpandedMonarchLanguageRule = Object::new().unchecked_into();
+        match self {
+            Self::Include { include } => rule.set_include(Some(include)),
+            Self::Match { regex, action } => {
+                rule.set_regex(&JsValue::from_str(regex));
+                rule.set_action(&action.to_js_value());
+            }
+        }
+        rule
+    }
+}
+
+/// Also accepts the short array forms `[regex, action]` and
+/// `[regex, action, next]`.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum MonarchRuleRepr {
+    Include {
+        include: String,
+    },
+    Match {
+        regex: String,
+        action: MonarchAction,
+    },
+    ShortNext(String, MonarchAction, String),
+    Short(String, MonarchAction),
+}
+#[cfg(feature = "serde")]
+impl TryFrom<MonarchRuleRepr> for MonarchRule {
+    type Error = &'static str;
+
+    fn try_from(repr: MonarchRuleRepr) -> Result<Self, Self::Error> {
+        Ok(match repr {
+            MonarchRuleRepr::Include { include } => Self::Include { include },
+            MonarchRuleRepr::Match { regex, action } | MonarchRuleRepr::Short(regex, action) => {
+                Self::Match { regex, action }
+            }
+            MonarchRuleRepr::ShortNext(regex, action, next) => {
+                let action = match action {
+                    MonarchAction::Token(token) => ExpandedMonarchAction::token(token),
+                    MonarchAction::Expanded(action) => *action,
+                    MonarchAction::Group(_) => {
+                        return Err(
+                            "the short rule form can't combine a group action with a next state",
+                        )
+                    }
+                };
+                Self::Match {
+                    regex,
+                    action: action.with_next(next).into(),
+                }
+            }
+        })
+    }
+}
+
+/// A bracket definition, for example `{ open: "{", close: "}", token:
+/// "delimiter.curly" }`.
+///
+/// With the "serde" feature this can also be deserialized from the short
+/// array form `["{", "}", "delimiter.curly"]`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "MonarchBracketRepr"))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MonarchBracket {
+    pub open: String,
+    pub close: String,
+    pub token: String,
+}
+impl MonarchBracket {
+    pub fn new(
+        open: impl Into<String>,
+        close: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            open: open.into(),
+            close: close.into(),
+            token: token.into(),
+        }
+    }
+
+    /// Convert into [`IMonarchLanguageBracket`].
+    pub fn to_sys_bracket(&self) -> IMonarchLanguageBracket {
+        let bracket: IMonarchLanguageBracket = Object::new().unchecked_into();
+        bracket.set_open(&self.open);
+        bracket.set_close(&self.close);
+        bracket.set_token(&self.token);
+        bracket
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum MonarchBracketRepr {
+    Object {
+        open: String,
+        close: String,
+        token: String,
+    },
+    Short(String, String, String),
+}
+#[cfg(feature = "serde")]
+impl From<MonarchBracketRepr> for MonarchBracket {
+    fn from(repr: MonarchBracketRepr) -> Self {
+        match repr {
+            MonarchBracketRepr::Object { open, close, token }
+            | MonarchBracketRepr::Short(open, close, token) => Self { open, close, token },
+        }
+    }
+}
+
+/// A custom attribute of a Monarch language, like a list of keywords.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MonarchAttribute {
+    /// A list of words which can be used as a guard in cases, e.g.
+    /// `@keywords`.
+    List(Vec<String>),
+    /// A regular expression which can be referenced in rules, e.g.
+    /// `@symbols`.
+    Regex(String),
+}
+impl MonarchAttribute {
+    /// Convert to the Javascript value Monarch expects.
+    /// Fails if a [`Regex`](Self::Regex) isn't a valid regular expression.
+    pub fn to_js_value(&self) -> Result<JsValue, JsValue> {
+        Ok(match self {
+            Self::List(words) => words.iter().map(JsValue::from).collect::<Array>().into(),
+            Self::Regex(pattern) => to_js_regex(pattern)?.into(),
+        })
+    }
+}
+
+/// Serde helpers for the custom attributes of a [`MonarchLanguage`].
+///
+/// The attributes share the object with the known members, so a member with
+/// the wrong case would silently become an attribute. Names that only differ
+/// from a known member in case are rejected instead.
+#[cfg(feature = "serde")]
+mod attributes {
+    use super::MonarchAttribute;
+    use serde::{de::Error, Deserialize, Deserializer};
+    use std::collections::BTreeMap;
+
+    const MEMBERS: &[&str] = &[
+        "tokenizer",
+        "ignoreCase",
+        "unicode",
+        "defaultToken",
+        "brackets",
+        "start",
+        "tokenPostfix",
+        "includeLF",
+    ];
+
+    /// Get the known member `name` differs from only in case.
+    fn misspelled_member(name: &str) -> Option<&'static str> {
+        MEMBERS
+            .iter()
+            .copied()
+            .find(|member| member.eq_ignore_ascii_case(name))
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, MonarchAttribute>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let attributes = BTreeMap::<String, MonarchAttribute>::deserialize(deserializer)?;
+        for name in attributes.keys() {
+            if let Some(member) = misspelled_member(name) {
+                return Err(D::Error::custom(format_args!(
+                    "unknown field `{}`, did you mean `{}`?",
+                    name, member
+                )));
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// A Monarch language definition.
+/// This is the typed version of [`IMonarchLanguage`].
+///
+/// With the "serde" feature this can be deserialized from the same JSON
+/// structure Monarch uses (with regular expressions given as strings).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MonarchLanguage {
+    /// The tokenizer states and their rules.
+    /// Unless [`start`](Self::start) is given, the first state is used.
+    #[cfg_attr(feature = "serde", serde(with = "ordered_map"))]
+    pub tokenizer: Vec<(String, Vec<MonarchRule>)>,
+    /// Is the language case insensitive?
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub ignore_case: Option<bool>,
+    /// Is the language unicode-aware? (i.e., /\u{1D306}/)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub unicode: Option<bool>,
+    /// If no match in the tokenizer assign this token class (default
+    /// 'source').
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub default_token: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub brackets: Vec<MonarchBracket>,
+    /// Start symbol in the tokenizer.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub start: Option<String>,
+    /// Attach this to every token class (by default '.' + name).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub token_postfix: Option<String>,
+    /// Include line feeds (in the form of a \n character) at the end of
+    /// lines.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "includeLF", default, skip_serializing_if = "Option::is_none")
+    )]
+    pub include_lf: Option<bool>,
+    /// Custom attributes that can be referenced by the rules.
+    ///
+    /// When deserializing, names that only differ from one of the members
+    /// above in case are rejected.
+    #[cfg_attr(
+        feature = "serde",
+        serde(flatten, deserialize_with = "attributes::deserialize")
+    )]
+    pub attributes: BTreeMap<String, MonarchAttribute>,
+}
+impl MonarchLanguage {
+    builder_methods! {
+        pub with ignore_case(bool);
+        pub with unicode(bool);
+        pub with default_token(String);
+        pub with start(String);
+        pub with token_postfix(String);
+        pub with include_lf(bool);
+    }
+
+    /// Add a tokenizer state.
+    pub fn with_state(mut self, name: impl Into<String>, rules: Vec<MonarchRule>) -> Self {
+        self.tokenizer.push((name.into(), rules));
+        self
+    }
+
+    pub fn with_bracket(mut self, bracket: MonarchBracket) -> Self {
+        self.brackets.push(bracket);
+        self
+    }
+
+    pub fn with_attribute(mut self, name: impl Into<String>, attribute: MonarchAttribute) -> Self {
+        self.attributes.insert(name.into(), attribute);
+        self
+    }
+
+    /// Convert into [`IMonarchLanguage`].
+    /// Fails if one of the regex attributes isn't a valid regular expression.
+    pub fn to_sys_language(&self) -> Result<IMonarchLanguage, JsValue> {
+        let language: IMonarchLanguage = Object::new().unchecked_into();
+
+        // attributes go first so they can't overwrite the known members
+        for (name, attribute) in &self.attributes {
+            set_key(&language, name, &attribute.to_js_value()?);
+        }
+
+        let tokenizer = Object::new();
+        for (state, rules) in &self.tokenizer {
+            let rules: Array = rules.iter().map(MonarchRule::to_sys_rule).collect();
+            set_key(&tokenizer, state, &rules);
+        }
+        language.set_tokenizer(&tokenizer);
+
+        language.set_ignore_case(self.ignore_case);
+        language.set_unicode(self.unicode);
+        language.set_default_token(self.default_token.as_deref());
+        if !self.brackets.is_empty() {
+            let brackets: Array = self
+                .brackets
+                .iter()
+                .map(MonarchBracket::to_sys_bracket)
+                .collect();
+            language.set_brackets(Some(&brackets));
+        }
+        language.set_start(self.start.as_deref());
+        language.set_token_postfix(self.token_postfix.as_deref());
+        language.set_include_lf(self.include_lf);
+        Ok(language)
+    }
+}
+
+impl TryFrom<&MonarchLanguage> for IMonarchLanguage {
+    type Error = JsValue;
+
+    fn try_from(language: &MonarchLanguage) -> Result<Self, Self::Error> {
+        language.to_sys_language()
+    }
+}
+
+/// Set the Monarch tokens provider for a language.
+///
+/// Fails if one of the regex attributes of `language` isn't a valid regular
+/// expression.
+pub fn set_monarch_tokens_provider(
+    language_id: &str,
+    language: &MonarchLanguage,
+) -> Result<IDisposable, JsValue> {
+    let language = language.to_sys_language()?;
+    Ok(languages::set_monarch_tokens_provider(
+        language_id,
+        &language,
+    ))
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    // parsed from strings because `serde_json::Value` doesn't keep the key
+    // order the tokenizer states depend on
+    fn parse(json: &str) -> Result<MonarchLanguage, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn round_trip() {
+        let language = MonarchLanguage::default()
+            .with_default_token("invalid".to_owned())
+            .with_token_postfix(".test".to_owned())
+            .with_bracket(MonarchBracket::new("{", "}", "delimiter.curly"))
+            .with_attribute(
+                "keywords",
+                MonarchAttribute::List(vec!["fn".to_owned(), "let".to_owned()]),
+            )
+            .with_attribute("symbols", MonarchAttribute::Regex("[=><!]+".to_owned()))
+            .with_state(
+                "root",
+                vec![
+                    MonarchRule::new(
+                        "[a-z_]+",
+                        MonarchAction::cases(vec![
+                            ("@keywords", "keyword"),
+                            ("@default", "identifier"),
+                        ]),
+                    ),
+                    MonarchRule::new(
+                        "\"",
+                        ExpandedMonarchAction::token("string").with_next("@string"),
+                    ),
+                    MonarchRule::include("@whitespace"),
+                ],
+            )
+            .with_state(
+                "string",
+                vec![MonarchRule::new(
+                    "\"",
+                    ExpandedMonarchAction::token("string")
+                        .with_bracket(MonarchBracketAction::Close)
+                        .with_next("@pop"),
+                )],
+            )
+            .with_state("whitespace", vec![MonarchRule::new("[ \\t]+", "white")]);
+
+        let json = serde_json::to_string(&language).unwrap();
+        assert_eq!(parse(&json).unwrap(), language);
+    }
+
+    #[test]
+    fn state_order_is_kept() {
+        let language = parse(r#"{ "tokenizer": { "zeta": [], "alpha": [], "mid": [] } }"#).unwrap();
+        let states: Vec<_> = language
+            .tokenizer
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(states, ["zeta", "alpha", "mid"]);
+    }
+
+    #[test]
+    fn short_forms() {
+        let language = parse(
+            r#"{
+                "brackets": [
+                    ["{", "}", "delimiter.curly"],
+                    { "open": "[", "close": "]", "token": "delimiter.square" }
+                ],
+                "tokenizer": {
+                    "root": [
+                        ["\"", "string", "@string"],
+                        ["\\d+", "number"],
+                        { "include": "@whitespace" }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            language.brackets,
+            [
+                MonarchBracket::new("{", "}", "delimiter.curly"),
+                MonarchBracket::new("[", "]", "delimiter.square"),
+            ]
+        );
+        assert_eq!(
+            language.tokenizer[0].1,
+            [
+                MonarchRule::new(
+                    "\"",
+                    ExpandedMonarchAction::token("string").with_next("@string")
+                ),
+                MonarchRule::new("\\d+", "number"),
+                MonarchRule::include("@whitespace"),
+            ]
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let language =
+            parse(r#"{ "keywords": ["if", "else"], "escapes": "\\\\[nt]", "tokenizer": {} }"#)
+                .unwrap();
+        assert_eq!(
+            language.attributes["keywords"],
+            MonarchAttribute::List(vec!["if".to_owned(), "else".to_owned()])
+        );
+        assert_eq!(
+            language.attributes["escapes"],
+            MonarchAttribute::Regex("\\\\[nt]".to_owned())
+        );
+    }
+
+    #[test]
+    fn misspelled_members_are_rejected() {
+        for key in ["Start", "ignorecase", "TOKENPOSTFIX", "includeLf"] {
+            let json = format!(r#"{{ "{}": ".x", "tokenizer": {{}} }}"#, key);
+            let err = parse(&json).unwrap_err();
+            assert!(err.to_string().contains("did you mean"), "{}: {}", key, err);
+        }
+    }
+
+    #[test]
+    fn attributes_close_to_members_are_accepted() {
+        let language = parse(r#"{ "stars": ["*"], "bracket": "[()]", "tokenizer": {} }"#).unwrap();
+        assert_eq!(
+            language.attributes["stars"],
+            MonarchAttribute::List(vec!["*".to_owned()])
+        );
+        assert_eq!(
+            language.attributes["bracket"],
+            MonarchAttribute::Regex("[()]".to_owned())
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let action = r#"{ "tokenizer": { "root": [["a", { "token": "a", "nxt": "@pop" }]] } }"#;
+        assert!(parse(action).is_err());
+
+        let rule = r#"{ "tokenizer": { "root": [{ "include": "@a", "regex": "b" }] } }"#;
+        assert!(parse(rule).is_err());
+
+        let bracket = r#"{
+            "brackets": [{ "open": "(", "close": ")", "token": "paren", "kind": 1 }],
+            "tokenizer": {}
+        }"#;
+        assert!(parse(bracket).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Deserialize;
+use std::{collections::BTreeMap, error::Error, fmt};
+
+/// Error returned by the TextMate grammar support.
+#[derive(Debug)]
+pub enum TextMateError {
+    /// The grammar isn't valid JSON or doesn't have the expected shape.
+    Json(serde_json::Error),
+    /// No grammar with the given scope name was added to the registry.
+    UnknownGrammar(String),
+}
+impl fmt::Display for TextMateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid grammar: {}", err),
+            Self::UnknownGrammar(scope_name) => write!(f, "unknown grammar {:?}", scope_name),
+        }
+    }
+}
+impl Error for TextMateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::UnknownGrammar(_) => None,
+        }
+    }
+}
+impl From<serde_json::Error> for TextMateError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// A capture of a [`TextMateRule`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct TextMateCapture {
+    /// Scope assigned to the captured text.
+    pub name: Option<String>,
+    /// Patterns applied to the captured text.
+    #[serde(default)]
+    pub patterns: Vec<TextMateRule>,
+}
+
+/// A rule of a [`TextMateGrammar`].
+///
+/// This is the raw form as it appears in `.tmLanguage.json` files.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextMateRule {
+    /// Reference to another rule: `#name` for a repository entry, `$self`,
+    /// `$base` or the scope name of another grammar.
+    pub include: Option<String>,
+    pub name: Option<String>,
+    /// Scope assigned to the text between `begin` and `end`.
+    pub content_name: Option<String>,
+    #[serde(rename = "match")]
+    pub match_: Option<String>,
+    pub begin: Option<String>,
+    pub end: Option<String>,
+    #[serde(rename = "while")]
+    pub while_: Option<String>,
+    #[serde(default)]
+    pub captures: BTreeMap<String, TextMateCapture>,
+    #[serde(default)]
+    pub begin_captures: BTreeMap<String, TextMateCapture>,
+    #[serde(default)]
+    pub end_captures: BTreeMap<String, TextMateCapture>,
+    #[serde(default)]
+    pub while_captures: BTreeMap<String, TextMateCapture>,
+    #[serde(default)]
+    pub patterns: Vec<TextMateRule>,
+    #[serde(default)]
+    pub repository: BTreeMap<String, TextMateRule>,
+    #[serde(default)]
+    pub apply_end_pattern_last: ApplyEndPatternLast,
+}
+
+/// The `applyEndPatternLast` flag, which grammars write as a bool or number.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(from = "ApplyEndPatternLastRepr")]
+pub struct ApplyEndPatternLast(pub bool);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApplyEndPatternLastRepr {
+    Bool(bool),
+    Number(i64),
+}
+impl From<ApplyEndPatternLastRepr> for ApplyEndPatternLast {
+    fn from(repr: ApplyEndPatternLastRepr) -> Self {
+        match repr {
+            ApplyEndPatternLastRepr::Bool(value) => Self(value),
+            ApplyEndPatternLastRepr::Number(value) => Self(value != 0),
+        }
+    }
+}
+
+/// A TextMate grammar, usually loaded from a `.tmLanguage.json` file.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextMateGrammar {
+    /// Root scope of the grammar, for example `source.rust`.
+    pub scope_name: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub file_types: Vec<String>,
+    pub first_line_match: Option<String>,
+    #[serde(default)]
+    pub patterns: Vec<TextMateRule>,
+    #[serde(default)]
+    pub repository: BTreeMap<String, TextMateRule>,
+}
+impl TextMateGrammar {
+    /// Parse a grammar in the `.tmLanguage.json` format.
+    pub fn from_json(json: &str) -> Result<Self, TextMateError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
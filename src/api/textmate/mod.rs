@@ -0,0 +1,17 @@
+//! TextMate grammar support.
+//! Requires the "textmate" feature.
+//!
+//! Grammars are interpreted in Rust and plugged into Monaco as encoded tokens
+//! providers, see [`GrammarRegistry`].
+//! The regex engine doesn't support every Oniguruma feature. Patterns it
+//! can't compile never match.
+pub use grammar::*;
+pub use registry::*;
+pub use scope_theme::*;
+pub use tokenizer::*;
+
+mod grammar;
+mod registry;
+mod rules;
+mod scope_theme;
+mod tokenizer;
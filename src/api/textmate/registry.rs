@@ -0,0 +1,165 @@
+use super::{
+    rules::CompiledGrammar,
+    ScopeTheme,
+    TextMateError,
+    TextMateGrammar,
+    TextMateTokenizer,
+};
+use crate::api::{encoded_language_id, set_encoded_tokens_provider, DisposableClosures, Theme};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    rc::Rc,
+};
+use wasm_bindgen::JsValue;
+
+#[derive(Default)]
+struct Inner {
+    grammars: HashMap<String, Rc<TextMateGrammar>>,
+    theme: Rc<ScopeTheme>,
+    /// Grammar scope name and tokens provider of each language.
+    languages: BTreeMap<String, (String, DisposableClosures)>,
+}
+
+/// Holds TextMate grammars and uses them to tokenize languages.
+///
+/// Tokens are styled by the registry's [`ScopeTheme`], so the editor has to
+/// use a theme defined with [`define_theme`](Self::define_theme) for the
+/// colors to be correct.
+///
+/// Cloning the registry returns a handle to the same registry.
+/// The tokens providers are removed when the last handle is dropped.
+#[derive(Clone, Default)]
+pub struct GrammarRegistry {
+    inner: Rc<RefCell<Inner>>,
+}
+impl GrammarRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a grammar, replacing any grammar with the same scope name.
+    ///
+    /// Grammars may include each other, so grammars should be added before
+    /// they're assigned to a language.
+    pub fn add_grammar(&self, grammar: TextMateGrammar) {
+        self.inner
+            .borrow_mut()
+            .grammars
+            .insert(grammar.scope_name.clone(), Rc::new(grammar));
+    }
+
+    /// Parse and add a grammar in the `.tmLanguage.json` format.
+    ///
+    /// Returns the scope name of the grammar.
+    pub fn add_grammar_json(&self, json: &str) -> Result<String, TextMateError> {
+        let grammar = TextMateGrammar::from_json(json)?;
+        let scope_name = grammar.scope_name.clone();
+        self.add_grammar(grammar);
+        Ok(scope_name)
+    }
+
+    /// Create a tokenizer for the grammar `scope_name`.
+    ///
+    /// `language_id` is the encoded language id used in the token metadata,
+    /// see [`encoded_language_id`].
+    pub fn tokenizer(
+        &self,
+        scope_name: &str,
+        language_id: u8,
+    ) -> Result<TextMateTokenizer, TextMateError> {
+        let inner = self.inner.borrow();
+        let grammar = CompiledGrammar::compile(scope_name, &inner.grammars)
+            .ok_or_else(|| TextMateError::UnknownGrammar(scope_name.to_owned()))?;
+        Ok(TextMateTokenizer::new(
+            Rc::new(grammar),
+            Rc::clone(&inner.theme),
+            language_id,
+        ))
+    }
+
+    /// Tokenize `language_id` using the grammar `scope_name`.
+    ///
+    /// This replaces any tokenizer that was previously set for the language.
+    pub fn set_language_grammar(
+        &self,
+        language_id: &str,
+        scope_name: &str,
+    ) -> Result<(), TextMateError> {
+        let provider = self.create_provider(language_id, scope_name)?;
+        let previous = self
+            .inner
+            .borrow_mut()
+            .languages
+            .insert(language_id.to_owned(), (scope_name.to_owned(), provider));
+        // the previous provider is disposed outside of the borrow
+        drop(previous);
+        Ok(())
+    }
+
+    /// Stop tokenizing `language_id` with this registry.
+    pub fn remove_language_grammar(&self, language_id: &str) {
+        let previous = self.inner.borrow_mut().languages.remove(language_id);
+        drop(previous);
+    }
+
+    /// The theme used to style tokens.
+    pub fn scope_theme(&self) -> Rc<ScopeTheme> {
+        Rc::clone(&self.inner.borrow().theme)
+    }
+
+    /// Style tokens using `theme`.
+    ///
+    /// All languages are tokenized again.
+    pub fn set_scope_theme(&self, theme: ScopeTheme) {
+        self.inner.borrow_mut().theme = Rc::new(theme);
+
+        let languages: Vec<(String, String)> = self
+            .inner
+            .borrow()
+            .languages
+            .iter()
+            .map(|(language_id, (scope_name, _))| (language_id.clone(), scope_name.clone()))
+            .collect();
+        for (language_id, scope_name) in languages {
+            // the grammar was compiled successfully before, and grammars are
+            // never removed
+            let _ = self.set_language_grammar(&language_id, &scope_name);
+        }
+    }
+
+    /// Define `theme` as `name` with the color map used by the registry, and
+    /// style tokens using it.
+    ///
+    /// Use [`set_global_theme`](crate::api::set_global_theme) to switch to it.
+    pub fn define_theme(&self, name: &str, theme: &Theme) -> Result<(), JsValue> {
+        let scope_theme = ScopeTheme::new(theme);
+        scope_theme.apply_to(theme.clone()).define(name)?;
+        self.set_scope_theme(scope_theme);
+        Ok(())
+    }
+
+    fn create_provider(
+        &self,
+        language_id: &str,
+        scope_name: &str,
+    ) -> Result<DisposableClosures, TextMateError> {
+        let tokenizer = self.tokenizer(scope_name, encoded_language_id(language_id))?;
+        Ok(set_encoded_tokens_provider(language_id, tokenizer))
+    }
+}
+impl fmt::Debug for GrammarRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+        let languages: BTreeMap<_, _> = inner
+            .languages
+            .iter()
+            .map(|(language_id, (scope_name, _))| (language_id, scope_name))
+            .collect();
+        f.debug_struct("GrammarRegistry")
+            .field("grammars", &inner.grammars.keys().collect::<Vec<_>>())
+            .field("languages", &languages)
+            .finish_non_exhaustive()
+    }
+}
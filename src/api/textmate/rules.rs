@@ -0,0 +1,412 @@
+//! Compilation of [`TextMateGrammar`]s into a flat list of rules.
+use super::{TextMateCapture, TextMateGrammar, TextMateRule};
+use fancy_regex::{Captures, Regex};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+pub(super) type RuleId = usize;
+
+type Repository = BTreeMap<String, TextMateRule>;
+
+/// A capture of a rule.
+#[derive(Clone)]
+pub(super) struct Capture {
+    pub name: Option<String>,
+    /// Rule whose patterns tokenize the captured text.
+    pub patterns: Option<RuleId>,
+}
+
+/// The captures of a rule, indexed by group number.
+pub(super) type CaptureRules = Vec<Option<Capture>>;
+
+/// A compiled Oniguruma pattern.
+pub(super) struct Pattern {
+    regex: Option<Regex>,
+    /// The pattern with `\G` never matching, used when a search doesn't start
+    /// at the anchor. `None` if the pattern doesn't use `\G`.
+    unanchored: Option<Option<Regex>>,
+}
+impl Pattern {
+    /// Compile an Oniguruma pattern.
+    ///
+    /// Patterns that use features the regex engine doesn't support never
+    /// match.
+    pub fn new(source: &str) -> Self {
+        let (translated, uses_anchor) = translate_oniguruma(source, true);
+        Self {
+            regex: Regex::new(&translated).ok(),
+            unanchored: uses_anchor.then(|| Regex::new(&translate_oniguruma(source, false).0).ok()),
+        }
+    }
+
+    /// Find the first match starting at or after `pos`.
+    ///
+    /// `\G` only matches at `pos` and only if `at_anchor` is set, which is
+    /// the case when `pos` is where the last begin or while match ended.
+    pub fn search<'t>(&self, text: &'t str, pos: usize, at_anchor: bool) -> Option<Captures<'t>> {
+        let regex = match &self.unanchored {
+            Some(unanchored) if !at_anchor => unanchored,
+            _ => &self.regex,
+        };
+        regex.as_ref()?.captures_from_pos(text, pos).ok().flatten()
+    }
+}
+
+pub(super) enum Rule {
+    Match {
+        regex: Pattern,
+        name: Option<String>,
+        captures: CaptureRules,
+    },
+    BeginEnd {
+        begin: Pattern,
+        end: String,
+        name: Option<String>,
+        content_name: Option<String>,
+        begin_captures: CaptureRules,
+        end_captures: CaptureRules,
+        patterns: Vec<RuleId>,
+        apply_end_pattern_last: bool,
+    },
+    BeginWhile {
+        begin: Pattern,
+        while_: String,
+        name: Option<String>,
+        content_name: Option<String>,
+        begin_captures: CaptureRules,
+        while_captures: CaptureRules,
+        patterns: Vec<RuleId>,
+    },
+    /// A rule that only groups other rules.
+    Patterns(Vec<RuleId>),
+}
+
+/// A grammar together with all the grammars it includes, compiled into rules.
+pub(super) struct CompiledGrammar {
+    pub scope_name: String,
+    pub root: RuleId,
+    rules: Vec<Rule>,
+    /// Leaf rules (rules that can match) reachable from a rule's patterns.
+    leaves: RefCell<HashMap<RuleId, Rc<[RuleId]>>>,
+    /// `end` and `while` patterns with back-references resolved.
+    dynamic: RefCell<HashMap<String, Rc<Pattern>>>,
+}
+impl CompiledGrammar {
+    /// Compile the grammar `scope_name`, resolving includes of other grammars
+    /// using `grammars`.
+    pub fn compile(
+        scope_name: &str,
+        grammars: &HashMap<String, Rc<TextMateGrammar>>,
+    ) -> Option<Self> {
+        let mut compiler = Compiler {
+            grammars,
+            base: scope_name,
+            rules: Vec::new(),
+            ids: HashMap::new(),
+            roots: HashMap::new(),
+        };
+        let root = compiler.compile_root(scope_name)?;
+        Some(Self {
+            scope_name: scope_name.to_owned(),
+            root,
+            rules: compiler.rules,
+            leaves: RefCell::default(),
+            dynamic: RefCell::default(),
+        })
+    }
+
+    pub fn rule(&self, id: RuleId) -> &Rule {
+        &self.rules[id]
+    }
+
+    /// The rules that need to be tried inside of rule `id`.
+    pub fn leaves(&self, id: RuleId) -> Rc<[RuleId]> {
+        if let Some(leaves) = self.leaves.borrow().get(&id) {
+            return Rc::clone(leaves);
+        }
+
+        let patterns = match self.rule(id) {
+            Rule::BeginEnd { patterns, .. }
+            | Rule::BeginWhile { patterns, .. }
+            | Rule::Patterns(patterns) => patterns.as_slice(),
+            Rule::Match { .. } => &[],
+        };
+        let mut leaves = Vec::new();
+        let mut visited = Vec::new();
+        self.collect_leaves(patterns, &mut leaves, &mut visited);
+        let leaves: Rc<[RuleId]> = leaves.into();
+        self.leaves.borrow_mut().insert(id, Rc::clone(&leaves));
+        leaves
+    }
+
+    fn collect_leaves(
+        &self,
+        patterns: &[RuleId],
+        leaves: &mut Vec<RuleId>,
+        visited: &mut Vec<RuleId>,
+    ) {
+        for &id in patterns {
+            match self.rule(id) {
+                Rule::Patterns(patterns) => {
+                    if !visited.contains(&id) {
+                        visited.push(id);
+                        self.collect_leaves(patterns, leaves, visited);
+                    }
+                }
+                _ => {
+                    if !leaves.contains(&id) {
+                        leaves.push(id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the compiled pattern for a resolved `end` or `while` pattern.
+    pub fn dynamic_pattern(&self, source: &str) -> Rc<Pattern> {
+        if let Some(pattern) = self.dynamic.borrow().get(source) {
+            return Rc::clone(pattern);
+        }
+        let pattern = Rc::new(Pattern::new(source));
+        self.dynamic
+            .borrow_mut()
+            .insert(source.to_owned(), Rc::clone(&pattern));
+        pattern
+    }
+}
+
+struct Compiler<'g> {
+    grammars: &'g HashMap<String, Rc<TextMateGrammar>>,
+    /// Scope name of the grammar `$base` refers to.
+    base: &'g str,
+    rules: Vec<Rule>,
+    ids: HashMap<*const TextMateRule, RuleId>,
+    roots: HashMap<&'g str, RuleId>,
+}
+impl<'g> Compiler<'g> {
+    fn alloc(&mut self) -> RuleId {
+        self.rules.push(Rule::Patterns(Vec::new()));
+        self.rules.len() - 1
+    }
+
+    fn compile_root(&mut self, scope_name: &str) -> Option<RuleId> {
+        let grammar: &'g TextMateGrammar = self.grammars.get(scope_name)?;
+        if let Some(&id) = self.roots.get(grammar.scope_name.as_str()) {
+            return Some(id);
+        }
+        let id = self.alloc();
+        self.roots.insert(&grammar.scope_name, id);
+        let patterns = self.compile_list(&grammar.patterns, grammar, &[&grammar.repository]);
+        self.rules[id] = Rule::Patterns(patterns);
+        Some(id)
+    }
+
+    fn compile_list(
+        &mut self,
+        rules: &'g [TextMateRule],
+        grammar: &'g TextMateGrammar,
+        repositories: &[&'g Repository],
+    ) -> Vec<RuleId> {
+        rules
+            .iter()
+            .filter_map(|rule| self.compile_rule(rule, grammar, repositories))
+            .collect()
+    }
+
+    fn compile_rule(
+        &mut self,
+        rule: &'g TextMateRule,
+        grammar: &'g TextMateGrammar,
+        repositories: &[&'g Repository],
+    ) -> Option<RuleId> {
+        if let Some(include) = &rule.include {
+            return self.resolve_include(include, grammar, repositories);
+        }
+        if let Some(&id) = self.ids.get(&(rule as *const _)) {
+            return Some(id);
+        }
+
+        let id = self.alloc();
+        self.ids.insert(rule, id);
+
+        let mut repositories = repositories.to_vec();
+        if !rule.repository.is_empty() {
+            repositories.push(&rule.repository);
+        }
+        let patterns = self.compile_list(&rule.patterns, grammar, &repositories);
+
+        let rule_captures = self.compile_captures(&rule.captures, grammar, &repositories);
+        // `captures` applies to begin, end and while if they don't have their
+        // own
+        let mut captures = |captures: &'g BTreeMap<String, TextMateCapture>| {
+            if captures.is_empty() {
+                rule_captures.clone()
+            } else {
+                self.compile_captures(captures, grammar, &repositories)
+            }
+        };
+        let begin_captures = captures(&rule.begin_captures);
+        let end_captures = captures(&rule.end_captures);
+        let while_captures = captures(&rule.while_captures);
+
+        self.rules[id] = match (&rule.match_, &rule.begin) {
+            (Some(match_), _) => Rule::Match {
+                regex: Pattern::new(match_),
+                name: rule.name.clone(),
+                captures: rule_captures,
+            },
+            (None, Some(begin)) => match &rule.while_ {
+                Some(while_) => Rule::BeginWhile {
+                    begin: Pattern::new(begin),
+                    while_: while_.clone(),
+                    name: rule.name.clone(),
+                    content_name: rule.content_name.clone(),
+                    begin_captures,
+                    while_captures,
+                    patterns,
+                },
+                None => Rule::BeginEnd {
+                    begin: Pattern::new(begin),
+                    // a missing end never matches
+                    end: rule.end.clone().unwrap_or_else(|| "\u{FFFF}".to_owned()),
+                    name: rule.name.clone(),
+                    content_name: rule.content_name.clone(),
+                    begin_captures,
+                    end_captures,
+                    patterns,
+                    apply_end_pattern_last: rule.apply_end_pattern_last.0,
+                },
+            },
+            (None, None) => Rule::Patterns(patterns),
+        };
+        Some(id)
+    }
+
+    fn compile_captures(
+        &mut self,
+        captures: &'g BTreeMap<String, TextMateCapture>,
+        grammar: &'g TextMateGrammar,
+        repositories: &[&'g Repository],
+    ) -> CaptureRules {
+        let mut compiled = CaptureRules::new();
+        for (group, capture) in captures {
+            if let Ok(group) = group.parse::<usize>() {
+                if compiled.len() <= group {
+                    compiled.resize(group + 1, None);
+                }
+                let patterns = if capture.patterns.is_empty() {
+                    None
+                } else {
+                    let id = self.alloc();
+                    let patterns = self.compile_list(&capture.patterns, grammar, repositories);
+                    self.rules[id] = Rule::Patterns(patterns);
+                    Some(id)
+                };
+                compiled[group] = Some(Capture {
+                    name: capture.name.clone(),
+                    patterns,
+                });
+            }
+        }
+        compiled
+    }
+
+    fn resolve_include(
+        &mut self,
+        include: &'g str,
+        grammar: &'g TextMateGrammar,
+        repositories: &[&'g Repository],
+    ) -> Option<RuleId> {
+        match include {
+            "$self" => self.compile_root(&grammar.scope_name),
+            "$base" => self.compile_root(self.base),
+            _ => {
+                if let Some(name) = include.strip_prefix('#') {
+                    let rule = repositories.iter().rev().find_map(|repo| repo.get(name))?;
+                    return self.compile_rule(rule, grammar, repositories);
+                }
+                match include.split_once('#') {
+                    Some((scope_name, name)) => {
+                        let other: &'g TextMateGrammar = self.grammars.get(scope_name)?;
+                        let rule = other.repository.get(name)?;
+                        self.compile_rule(rule, other, &[&other.repository])
+                    }
+                    None => self.compile_root(include),
+                }
+            }
+        }
+    }
+}
+
+/// Rewrite the Oniguruma-only syntax used by grammars.
+///
+/// `\G` is kept if `allow_anchor` is set and never matches otherwise.
+/// Also returns whether the pattern uses `\G`.
+fn translate_oniguruma(source: &str, allow_anchor: bool) -> (String, bool) {
+    // `^` and `$` always match at line boundaries in Oniguruma
+    let mut out = String::with_capacity(source.len() + 4);
+    out.push_str("(?m)");
+    let mut uses_anchor = false;
+    let mut chars = source.chars();
+    // nesting depth of character classes
+    let mut class_depth = 0_usize;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('h') if class_depth > 0 => out.push_str("0-9a-fA-F"),
+                Some('h') => out.push_str("[0-9a-fA-F]"),
+                Some('H') if class_depth == 0 => out.push_str("[^0-9a-fA-F]"),
+                Some('Z') if class_depth == 0 => out.push_str("(?=\\n?\\z)"),
+                Some('G') if class_depth == 0 => {
+                    uses_anchor = true;
+                    out.push_str(if allow_anchor { "\\G" } else { "(?!)" });
+                }
+                Some(escaped) => {
+                    out.push('\\');
+                    out.push(escaped);
+                }
+                None => out.push('\\'),
+            },
+            '[' => {
+                class_depth += 1;
+                out.push(c);
+            }
+            ']' => {
+                class_depth = class_depth.saturating_sub(1);
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    (out, uses_anchor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate() {
+        assert_eq!(
+            translate_oniguruma(r"\h+[\h_]\H\Z", true),
+            (
+                r"(?m)[0-9a-fA-F]+[0-9a-fA-F_][^0-9a-fA-F](?=\n?\z)".to_owned(),
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn anchor_only_matches_at_anchor() {
+        let pattern = Pattern::new(r"\Ga");
+        assert!(pattern.search("xa", 1, true).is_some());
+        assert!(pattern.search("xa", 1, false).is_none());
+        assert!(pattern.search("xa", 0, true).is_none());
+
+        let pattern = Pattern::new(r"[\G]");
+        assert!(pattern.unanchored.is_none());
+    }
+}
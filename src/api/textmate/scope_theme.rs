@@ -0,0 +1,162 @@
+use crate::{
+    api::{Color, ColorId, FontStyle, StandardTokenType, Theme, TokenMetadata},
+    sys::editor::BuiltinTheme,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+struct ScopeRule {
+    scope: String,
+    foreground: Option<u16>,
+    background: Option<u16>,
+    font_style: Option<FontStyle>,
+}
+impl ScopeRule {
+    /// How well the rule matches `scopes`, `None` if it doesn't match.
+    ///
+    /// Rules matching deeper scopes win, followed by rules with more
+    /// specific selectors.
+    fn score(&self, scopes: &[String]) -> Option<(usize, usize)> {
+        scopes.iter().enumerate().rev().find_map(|(depth, scope)| {
+            let matches = scope == &self.scope
+                || (scope.starts_with(&self.scope)
+                    && scope.as_bytes().get(self.scope.len()) == Some(&b'.'));
+            matches.then_some((depth, self.scope.len()))
+        })
+    }
+}
+
+/// Maps the TextMate scopes of tokens to their style in a [`Theme`].
+///
+/// The token rules of the theme are interpreted as scope selectors: a rule
+/// applies to all scopes it is a prefix of. Each style property is taken
+/// from the best matching rule that sets it.
+///
+/// Colors are referenced by their id in [`color_map`](Self::color_map), so
+/// the theme has to be defined with this color map using
+/// [`apply_to`](Self::apply_to).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopeTheme {
+    rules: Vec<ScopeRule>,
+    colors: Vec<Color>,
+    foreground: u16,
+    background: u16,
+    font_style: FontStyle,
+}
+impl ScopeTheme {
+    pub fn new(theme: &Theme) -> Self {
+        let (foreground, background) = match theme.base {
+            BuiltinTheme::Vs => (Color::rgb(0x00, 0x00, 0x00), Color::rgb(0xff, 0xff, 0xff)),
+            BuiltinTheme::VsDark => (Color::rgb(0xd4, 0xd4, 0xd4), Color::rgb(0x1e, 0x1e, 0x1e)),
+            BuiltinTheme::HcBlack => (Color::rgb(0xff, 0xff, 0xff), Color::rgb(0x00, 0x00, 0x00)),
+        };
+        let color = |id: ColorId| theme.colors.get(id.to_value()).copied();
+        let mut foreground = color(ColorId::EditorForeground).unwrap_or(foreground);
+        let mut background = color(ColorId::EditorBackground).unwrap_or(background);
+        let mut font_style = FontStyle::NONE;
+        for rule in theme.rules.iter().filter(|rule| rule.token.is_empty()) {
+            foreground = rule.foreground.unwrap_or(foreground);
+            background = rule.background.unwrap_or(background);
+            font_style = rule.font_style.unwrap_or(font_style);
+        }
+
+        let mut this = Self {
+            rules: Vec::new(),
+            colors: Vec::new(),
+            foreground: 0,
+            background: 0,
+            font_style,
+        };
+        this.foreground = this.color_id(foreground);
+        this.background = this.color_id(background);
+        for rule in theme.rules.iter().filter(|rule| !rule.token.is_empty()) {
+            let rule = ScopeRule {
+                scope: rule.token.clone(),
+                foreground: rule.foreground.map(|c| this.color_id(c)),
+                background: rule.background.map(|c| this.color_id(c)),
+                font_style: rule.font_style,
+            };
+            this.rules.push(rule);
+        }
+        this
+    }
+
+    /// Id of `color` in the color map, adding it if necessary.
+    fn color_id(&mut self, color: Color) -> u16 {
+        // Monaco ignores the alpha channel of token colors
+        let color = Color { a: 0xff, ..color };
+        let index = match self.colors.iter().position(|c| *c == color) {
+            Some(index) => index,
+            None => {
+                self.colors.push(color);
+                self.colors.len() - 1
+            }
+        };
+        // ids start at 1 as 0 means "no color"
+        index as u16 + 1
+    }
+
+    /// The colors referenced by the metadata, in the order Monaco assigns
+    /// their ids.
+    pub fn color_map(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Set the color map of `theme` so the colors of the metadata resolve
+    /// correctly.
+    pub fn apply_to(&self, theme: Theme) -> Theme {
+        theme.with_encoded_tokens_colors(self.colors.clone())
+    }
+
+    /// Get the metadata for a token with the given `scopes`, ordered from the
+    /// outermost to the innermost scope.
+    ///
+    /// The language id of the returned metadata is 0.
+    pub fn metadata(&self, scopes: &[String]) -> TokenMetadata {
+        fn best<T>(
+            rules: &[ScopeRule],
+            scopes: &[String],
+            property: impl Fn(&ScopeRule) -> Option<T>,
+        ) -> Option<T> {
+            let mut best = None;
+            for rule in rules {
+                if let (Some(value), Some(score)) = (property(rule), rule.score(scopes)) {
+                    // later rules win ties
+                    if best
+                        .as_ref()
+                        .is_none_or(|(best_score, _)| score >= *best_score)
+                    {
+                        best = Some((score, value));
+                    }
+                }
+            }
+            best.map(|(_, value)| value)
+        }
+
+        TokenMetadata {
+            language_id: 0,
+            token_type: standard_token_type(scopes),
+            font_style: best(&self.rules, scopes, |r| r.font_style).unwrap_or(self.font_style),
+            foreground: best(&self.rules, scopes, |r| r.foreground).unwrap_or(self.foreground),
+            background: best(&self.rules, scopes, |r| r.background).unwrap_or(self.background),
+        }
+    }
+}
+impl Default for ScopeTheme {
+    fn default() -> Self {
+        Self::new(&Theme::new(BuiltinTheme::Vs))
+    }
+}
+
+fn standard_token_type(scopes: &[String]) -> StandardTokenType {
+    for scope in scopes.iter().rev() {
+        let first = scope.split('.').next().unwrap_or_default();
+        match first {
+            "comment" => return StandardTokenType::Comment,
+            "string" if scope.starts_with("string.regexp") => return StandardTokenType::RegEx,
+            "string" => return StandardTokenType::String,
+            "regex" => return StandardTokenType::RegEx,
+            _ => {}
+        }
+    }
+    StandardTokenType::Other
+}
@@ -0,0 +1,792 @@
+use super::{
+    rules::{CaptureRules, CompiledGrammar, Rule, RuleId},
+    ScopeTheme,
+};
+use crate::api::{EncodedToken, EncodedTokenizer, TokenMetadata};
+use fancy_regex::Captures;
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+/// Number of zero-width matches in a row after which the rest of a line is
+/// skipped, protecting against grammars that loop without advancing.
+const MAX_ZERO_WIDTH_MATCHES: usize = 8;
+
+/// How deep captures are tokenized with their own patterns, protecting
+/// against grammars that capture the same text over and over.
+const MAX_CAPTURE_DEPTH: usize = 16;
+
+#[derive(Debug, Eq, PartialEq)]
+struct Frame {
+    parent: Option<Rc<Frame>>,
+    rule: RuleId,
+    /// The `end` or `while` pattern with back-references resolved.
+    end: Option<Rc<str>>,
+    /// Scopes of the begin and end tokens.
+    scopes: Rc<[String]>,
+    /// Scopes of the tokens between begin and end.
+    content_scopes: Rc<[String]>,
+    /// Whether the begin match reached the end of its line, making `\G` match
+    /// at the start of the next line.
+    begin_captured_eol: bool,
+}
+
+/// The state of a [`TextMateTokenizer`] at the end of a line.
+///
+/// This is the stack of rules that were entered but not left yet.
+/// States are cheap to clone and compare.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextMateState {
+    top: Rc<Frame>,
+}
+
+/// A token produced by [`TextMateTokenizer::tokenize_line_scopes`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScopedToken {
+    /// Offset of the first character of the token in UTF-16 code units.
+    pub start_index: u32,
+    /// Scopes of the token from the outermost to the innermost.
+    pub scopes: Rc<[String]>,
+}
+
+/// Tokenizes lines using a TextMate grammar.
+///
+/// Get one from
+/// [`GrammarRegistry::tokenizer`](super::GrammarRegistry::tokenizer).
+#[derive(Clone)]
+pub struct TextMateTokenizer {
+    grammar: Rc<CompiledGrammar>,
+    theme: Rc<ScopeTheme>,
+    language_id: u8,
+    metadata: Rc<RefCell<HashMap<Rc<[String]>, TokenMetadata>>>,
+}
+impl TextMateTokenizer {
+    pub(super) fn new(
+        grammar: Rc<CompiledGrammar>,
+        theme: Rc<ScopeTheme>,
+        language_id: u8,
+    ) -> Self {
+        Self {
+            grammar,
+            theme,
+            language_id,
+            metadata: Rc::default(),
+        }
+    }
+
+    /// Scope name of the grammar.
+    pub fn scope_name(&self) -> &str {
+        &self.grammar.scope_name
+    }
+
+    /// The state at the beginning of the document.
+    pub fn initial_state(&self) -> TextMateState {
+        let scopes: Rc<[String]> = Rc::new([self.grammar.scope_name.clone()]);
+        TextMateState {
+            top: Rc::new(Frame {
+                parent: None,
+                rule: self.grammar.root,
+                end: None,
+                scopes: Rc::clone(&scopes),
+                content_scopes: scopes,
+                begin_captured_eol: false,
+            }),
+        }
+    }
+
+    /// Tokenize a line given the state at the beginning of the line.
+    ///
+    /// Returns the tokens with all their scopes together with the state at the
+    /// end of the line.
+    pub fn tokenize_line_scopes(
+        &self,
+        line: &str,
+        state: &TextMateState,
+    ) -> (Vec<ScopedToken>, TextMateState) {
+        // grammars expect lines to end with a newline
+        let text = format!("{}\n", line);
+        let mut line_tokenizer = LineTokenizer::new(&self.grammar, &text, Rc::clone(&state.top), 0);
+        line_tokenizer.run();
+
+        let LineTokenizer { tokens, stack, .. } = line_tokenizer;
+        let utf16 = Utf16Offsets::new(line);
+        let mut tokens: Vec<ScopedToken> = tokens
+            .into_iter()
+            .filter(|(start, _)| *start < line.len() || *start == 0)
+            .map(|(start, scopes)| ScopedToken {
+                start_index: utf16.offset(start),
+                scopes,
+            })
+            .collect();
+        if tokens.is_empty() {
+            tokens.push(ScopedToken {
+                start_index: 0,
+                scopes: Rc::clone(&stack.content_scopes),
+            });
+        }
+        (tokens, TextMateState { top: stack })
+    }
+
+    fn metadata(&self, scopes: &Rc<[String]>) -> TokenMetadata {
+        let mut cache = self.metadata.borrow_mut();
+        let metadata = cache
+            .entry(Rc::clone(scopes))
+            .or_insert_with(|| self.theme.metadata(scopes));
+        TokenMetadata {
+            language_id: self.language_id,
+            ..*metadata
+        }
+    }
+}
+impl EncodedTokenizer for TextMateTokenizer {
+    type State = TextMateState;
+
+    fn initial_state(&self) -> Self::State {
+        TextMateTokenizer::initial_state(self)
+    }
+
+    fn tokenize_line_encoded(
+        &self,
+        line: &str,
+        state: &Self::State,
+    ) -> (Vec<EncodedToken>, Self::State) {
+        let (tokens, state) = self.tokenize_line_scopes(line, state);
+        let mut encoded: Vec<EncodedToken> = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let metadata = self.metadata(&token.scopes);
+            // merge tokens that look the same
+            if encoded.last().is_some_and(|last| last.metadata == metadata) {
+                continue;
+            }
+            encoded.push(EncodedToken::new(token.start_index, metadata));
+        }
+        (encoded, state)
+    }
+}
+impl fmt::Debug for TextMateTokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextMateTokenizer")
+            .field("scope_name", &self.grammar.scope_name)
+            .field("language_id", &self.language_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What matched next in the line.
+enum Matched {
+    /// The end pattern of the current rule.
+    End,
+    Rule(RuleId),
+}
+
+struct LineTokenizer<'a> {
+    grammar: &'a CompiledGrammar,
+    text: &'a str,
+    /// Start byte offset and scopes of each token.
+    tokens: Vec<(usize, Rc<[String]>)>,
+    stack: Rc<Frame>,
+    /// Where `\G` matches, the end of the last begin or while match.
+    anchor: Option<usize>,
+    /// The anchors to restore when the frames pushed on this line are popped.
+    saved_anchors: Vec<Option<usize>>,
+    /// Nesting depth of captures tokenized with their own patterns.
+    depth: usize,
+}
+impl<'a> LineTokenizer<'a> {
+    fn new(grammar: &'a CompiledGrammar, text: &'a str, stack: Rc<Frame>, depth: usize) -> Self {
+        Self {
+            grammar,
+            text,
+            tokens: Vec::new(),
+            stack,
+            anchor: None,
+            saved_anchors: Vec::new(),
+            depth,
+        }
+    }
+
+    fn run(&mut self) {
+        let pos = self.check_while_conditions();
+        self.run_from(pos);
+    }
+
+    fn run_from(&mut self, mut pos: usize) {
+        let mut zero_width_matches = 0;
+        while pos < self.text.len() {
+            let (matched, captures) = match self.find_next(pos) {
+                Some(found) => found,
+                None => break,
+            };
+            let whole = captures.get(0).expect("group 0 always exists");
+            let (start, end) = (whole.start(), whole.end());
+            self.push_token(pos, Rc::clone(&self.stack.content_scopes));
+
+            match matched {
+                Matched::End => {
+                    let frame = Rc::clone(&self.stack);
+                    let end_captures = match self.grammar.rule(frame.rule) {
+                        Rule::BeginEnd { end_captures, .. } => end_captures,
+                        _ => unreachable!("only begin/end rules have an end pattern"),
+                    };
+                    self.push_captures(&captures, &frame.scopes, None, end_captures);
+                    self.stack = frame.parent.clone().expect("the root frame never ends");
+                    // frames pushed on previous lines have no anchor
+                    self.anchor = self.saved_anchors.pop().flatten();
+                }
+                Matched::Rule(id) => self.apply_rule(id, &captures),
+            }
+
+            if end == start {
+                zero_width_matches += 1;
+                let is_match_rule = matches!(
+                    matched,
+                    Matched::Rule(id) if matches!(self.grammar.rule(id), Rule::Match { .. })
+                );
+                if is_match_rule || zero_width_matches > MAX_ZERO_WIDTH_MATCHES {
+                    // the grammar doesn't advance, give up on the rest of the line
+                    break;
+                }
+            } else {
+                zero_width_matches = 0;
+            }
+            pos = end;
+        }
+        self.push_token(pos, Rc::clone(&self.stack.content_scopes));
+    }
+
+    /// Pop `while` rules whose condition doesn't hold on this line.
+    ///
+    /// Returns the position after the matched conditions.
+    fn check_while_conditions(&mut self) -> usize {
+        let mut frames = Vec::new();
+        let mut frame = Some(Rc::clone(&self.stack));
+        while let Some(current) = frame {
+            frame = current.parent.clone();
+            frames.push(current);
+        }
+
+        let mut pos = 0;
+        self.anchor = self.stack.begin_captured_eol.then_some(0);
+        // from the outermost to the innermost frame
+        for frame in frames.into_iter().rev() {
+            let while_captures = match self.grammar.rule(frame.rule) {
+                Rule::BeginWhile { while_captures, .. } => while_captures,
+                _ => continue,
+            };
+            let captures = frame.end.as_deref().and_then(|source| {
+                self.grammar.dynamic_pattern(source).search(
+                    self.text,
+                    pos,
+                    self.anchor == Some(pos),
+                )
+            });
+            match captures {
+                Some(captures) => {
+                    self.push_captures(&captures, &frame.scopes, None, while_captures);
+                    pos = captures.get(0).expect("group 0 always exists").end();
+                    self.anchor = Some(pos);
+                }
+                None => {
+                    self.stack = frame
+                        .parent
+                        .clone()
+                        .expect("the root frame has no condition");
+                    break;
+                }
+            }
+        }
+        pos
+    }
+
+    fn find_next(&self, pos: usize) -> Option<(Matched, Captures<'a>)> {
+        let frame = &self.stack;
+        let end_pattern = frame
+            .end
+            .as_deref()
+            .map(|source| self.grammar.dynamic_pattern(source));
+        let end_last = match self.grammar.rule(frame.rule) {
+            Rule::BeginEnd {
+                apply_end_pattern_last,
+                ..
+            } => *apply_end_pattern_last,
+            // `while` patterns are only checked at the start of a line
+            Rule::BeginWhile { .. } => return self.find_rule(pos, None),
+            _ => false,
+        };
+
+        let at_anchor = self.anchor == Some(pos);
+        let end = end_pattern.and_then(|pattern| pattern.search(self.text, pos, at_anchor));
+        let rule = self.find_rule(pos, end.as_ref().map(|c| start_of(c)));
+        match (end, rule) {
+            (Some(end), Some(rule)) => {
+                let (end_start, rule_start) = (start_of(&end), start_of(&rule.1));
+                if rule_start < end_start || (rule_start == end_start && end_last) {
+                    Some(rule)
+                } else {
+                    Some((Matched::End, end))
+                }
+            }
+            (Some(end), None) => Some((Matched::End, end)),
+            (None, rule) => rule,
+        }
+    }
+
+    /// Find the rule matching first. Matches starting after `limit` are
+    /// ignored.
+    fn find_rule(&self, pos: usize, limit: Option<usize>) -> Option<(Matched, Captures<'a>)> {
+        let mut best: Option<(Matched, Captures<'a>)> = None;
+        let at_anchor = self.anchor == Some(pos);
+        for &id in self.grammar.leaves(self.stack.rule).iter() {
+            let pattern = match self.grammar.rule(id) {
+                Rule::Match { regex, .. }
+                | Rule::BeginEnd { begin: regex, .. }
+                | Rule::BeginWhile { begin: regex, .. } => regex,
+                Rule::Patterns(_) => continue,
+            };
+            let captures = match pattern.search(self.text, pos, at_anchor) {
+                Some(captures) => captures,
+                None => continue,
+            };
+            let start = start_of(&captures);
+            let best_start = best.as_ref().map(|(_, c)| start_of(c)).or(limit);
+            if best_start.is_none_or(|best_start| start < best_start)
+                || (best.is_none() && Some(start) == limit)
+            {
+                let at_pos = start == pos;
+                best = Some((Matched::Rule(id), captures));
+                // nothing can match earlier
+                if at_pos {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    fn apply_rule(&mut self, id: RuleId, captures: &Captures) {
+        let content_scopes = Rc::clone(&self.stack.content_scopes);
+        match self.grammar.rule(id) {
+            Rule::Match {
+                name,
+                captures: names,
+                ..
+            } => {
+                let name = name.as_deref().map(|n| resolve_name(n, captures));
+                self.push_captures(captures, &content_scopes, name.as_deref(), names);
+            }
+            Rule::BeginEnd {
+                end: source,
+                name,
+                content_name,
+                begin_captures,
+                ..
+            }
+            | Rule::BeginWhile {
+                while_: source,
+                name,
+                content_name,
+                begin_captures,
+                ..
+            } => {
+                let scopes = with_scopes(
+                    &content_scopes,
+                    name.as_deref()
+                        .map(|n| resolve_name(n, captures))
+                        .as_deref(),
+                );
+                self.push_captures(captures, &scopes, None, begin_captures);
+                let frame_content_scopes = with_scopes(
+                    &scopes,
+                    content_name
+                        .as_deref()
+                        .map(|n| resolve_name(n, captures))
+                        .as_deref(),
+                );
+                let end = captures.get(0).expect("group 0 always exists").end();
+                self.stack = Rc::new(Frame {
+                    parent: Some(Rc::clone(&self.stack)),
+                    rule: id,
+                    end: Some(resolve_back_references(source, captures).into()),
+                    scopes,
+                    content_scopes: frame_content_scopes,
+                    begin_captured_eol: end == self.text.len(),
+                });
+                self.saved_anchors.push(self.anchor);
+                self.anchor = Some(end);
+            }
+            Rule::Patterns(_) => {}
+        }
+    }
+
+    /// Push the tokens of a match, with the scope `name` covering the whole
+    /// match and the capture rules covering their groups.
+    fn push_captures(
+        &mut self,
+        captures: &Captures,
+        base: &Rc<[String]>,
+        name: Option<&str>,
+        rules: &CaptureRules,
+    ) {
+        let whole = captures.get(0).expect("group 0 always exists");
+        if whole.start() == whole.end() {
+            return;
+        }
+        let base = with_scopes(base, name);
+        self.push_token(whole.start(), Rc::clone(&base));
+
+        // scopes and end of the groups containing the current position,
+        // outermost first
+        let mut open: Vec<(Rc<[String]>, usize)> = Vec::new();
+        for (group, rule) in rules.iter().enumerate() {
+            let (rule, m) = match (rule, captures.get(group)) {
+                (Some(rule), Some(m)) => (rule, m),
+                _ => continue,
+            };
+            // groups in lookarounds can reach outside of the match
+            let (start, end) = (m.start().max(whole.start()), m.end().min(whole.end()));
+            if start >= end {
+                continue;
+            }
+            self.close_groups(&mut open, &base, start);
+            let name = rule.name.as_deref().map(|n| resolve_name(n, captures));
+
+            match rule.patterns {
+                Some(patterns) if self.depth < MAX_CAPTURE_DEPTH => {
+                    // tokenize the captured text as if it were the only text
+                    // left on the line
+                    let scopes = with_scopes(&base, name.as_deref());
+                    let stack = Rc::new(Frame {
+                        parent: Some(Rc::clone(&self.stack)),
+                        rule: patterns,
+                        end: None,
+                        scopes: Rc::clone(&scopes),
+                        content_scopes: scopes,
+                        begin_captured_eol: false,
+                    });
+                    let mut captured =
+                        LineTokenizer::new(self.grammar, &self.text[..end], stack, self.depth + 1);
+                    captured.run_from(start);
+                    for (token_start, scopes) in captured.tokens {
+                        self.push_token(token_start, scopes);
+                    }
+                    let outer = open.last().map_or(&base, |(scopes, _)| scopes);
+                    self.push_token(end, Rc::clone(outer));
+                }
+                _ => {
+                    if let Some(name) = name {
+                        let outer = open.last().map_or(&base, |(scopes, _)| scopes);
+                        let scopes = with_scopes(outer, Some(&name));
+                        self.push_token(start, Rc::clone(&scopes));
+                        open.push((scopes, end));
+                    }
+                }
+            }
+        }
+        self.close_groups(&mut open, &base, whole.end());
+    }
+
+    /// Close the groups in `open` that end at or before `pos`.
+    fn close_groups(
+        &mut self,
+        open: &mut Vec<(Rc<[String]>, usize)>,
+        base: &Rc<[String]>,
+        pos: usize,
+    ) {
+        while let Some(&(_, end)) = open.last() {
+            if end > pos {
+                break;
+            }
+            open.pop();
+            let outer = open.last().map_or(base, |(scopes, _)| scopes);
+            self.push_token(end, Rc::clone(outer));
+        }
+    }
+
+    /// Start a new token at `start`, merging it with the previous token if the
+    /// scopes are the same.
+    fn push_token(&mut self, start: usize, scopes: Rc<[String]>) {
+        if let Some((last_start, last_scopes)) = self.tokens.last_mut() {
+            if *last_start == start {
+                *last_scopes = scopes;
+                return;
+            }
+            if *last_scopes == scopes {
+                return;
+            }
+        }
+        self.tokens.push((start, scopes));
+    }
+}
+
+fn start_of(captures: &Captures) -> usize {
+    captures.get(0).map_or(0, |m| m.start())
+}
+
+/// Append the space separated scopes in `name` to `scopes`.
+fn with_scopes(scopes: &Rc<[String]>, name: Option<&str>) -> Rc<[String]> {
+    match name {
+        Some(name) if !name.trim().is_empty() => scopes
+            .iter()
+            .cloned()
+            .chain(name.split_whitespace().map(str::to_owned))
+            .collect::<Vec<_>>()
+            .into(),
+        _ => Rc::clone(scopes),
+    }
+}
+
+/// Substitute `$n`, `${n:/downcase}` and `${n:/upcase}` in a scope name.
+fn resolve_name(name: &str, captures: &Captures) -> String {
+    if !name.contains('$') {
+        return name.to_owned();
+    }
+    let group = |index: &str| {
+        index
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| captures.get(i))
+            .map_or("", |m| m.as_str())
+            .to_owned()
+    };
+
+    let mut out = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(index) = rest.find('$') {
+        out.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+        if let Some(inner) = rest.strip_prefix('{') {
+            if let Some(close) = inner.find('}') {
+                let (index, command) = inner[..close]
+                    .split_once(":/")
+                    .unwrap_or((&inner[..close], ""));
+                let value = group(index);
+                out.push_str(&match command {
+                    "downcase" => value.to_lowercase(),
+                    "upcase" => value.to_uppercase(),
+                    _ => value,
+                });
+                rest = &inner[close + 1..];
+                continue;
+            }
+        }
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            out.push('$');
+        } else {
+            out.push_str(&group(&rest[..digits]));
+            rest = &rest[digits..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Substitute the back-references `\n` in an end pattern with the escaped
+/// text of the begin captures.
+fn resolve_back_references(source: &str, captures: &Captures) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some(d) if d.is_ascii_digit() => {
+                let mut index = String::new();
+                while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    index.push(*d);
+                    chars.next();
+                }
+                let text = index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| captures.get(i))
+                    .map_or("", |m| m.as_str());
+                out.push_str(&fancy_regex::escape(text));
+            }
+            Some(_) => {
+                out.push(c);
+                out.push(chars.next().expect("peeked"));
+            }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+/// Converts byte offsets of a line to UTF-16 offsets.
+struct Utf16Offsets(Option<Vec<(usize, u32)>>);
+impl Utf16Offsets {
+    fn new(line: &str) -> Self {
+        if line.is_ascii() {
+            return Self(None);
+        }
+        let mut offsets = Vec::with_capacity(line.len() + 1);
+        let mut offset = 0;
+        for (byte, c) in line.char_indices() {
+            offsets.push((byte, offset));
+            offset += c.len_utf16() as u32;
+        }
+        offsets.push((line.len(), offset));
+        Self(Some(offsets))
+    }
+
+    fn offset(&self, byte: usize) -> u32 {
+        match &self.0 {
+            None => byte as u32,
+            Some(offsets) => match offsets.binary_search_by_key(&byte, |(b, _)| *b) {
+                Ok(index) => offsets[index].1,
+                Err(index) => offsets[index.saturating_sub(1)].1,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::textmate::GrammarRegistry;
+
+    const FIXTURE: &str = include_str!("fixtures/test.tmLanguage.json");
+
+    /// Tokenize `lines`, returning the start and the scopes after
+    /// `source.test` of each token.
+    fn tokenize(lines: &[&str]) -> Vec<Vec<(u32, String)>> {
+        let registry = GrammarRegistry::new();
+        let scope_name = registry.add_grammar_json(FIXTURE).unwrap();
+        let tokenizer = registry.tokenizer(&scope_name, 0).unwrap();
+        let mut state = tokenizer.initial_state();
+        lines
+            .iter()
+            .map(|line| {
+                let (tokens, next) = tokenizer.tokenize_line_scopes(line, &state);
+                state = next;
+                tokens
+                    .into_iter()
+                    .map(|token| {
+                        assert_eq!(token.scopes[0], "source.test");
+                        (token.start_index, token.scopes[1..].join(" "))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn tokens(tokens: &[(u32, &str)]) -> Vec<(u32, String)> {
+        tokens
+            .iter()
+            .map(|(start, scopes)| (*start, (*scopes).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn begin_end_across_lines() {
+        assert_eq!(
+            tokenize(&["a /* b", "c */ d"]),
+            [
+                tokens(&[(0, ""), (2, "comment.block.test")]),
+                tokens(&[(0, "comment.block.test"), (4, "")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn captures() {
+        assert_eq!(
+            tokenize(&["let x"]),
+            [tokens(&[
+                (0, "meta.let.test keyword.test"),
+                (3, "meta.let.test"),
+                (4, "meta.let.test variable.X.test"),
+            ])]
+        );
+        assert_eq!(
+            tokenize(&["'a\\'b'"]),
+            [tokens(&[
+                (0, "string.quoted.test punctuation.begin.test"),
+                (1, "string.quoted.test"),
+                (2, "string.quoted.test constant.character.escape.test"),
+                (4, "string.quoted.test"),
+                (5, "string.quoted.test punctuation.end.test"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn back_references_in_end() {
+        assert_eq!(
+            tokenize(&["\"a'\" b"]),
+            [tokens(&[
+                (0, "string.quoted.test punctuation.begin.test"),
+                (1, "string.quoted.test"),
+                (3, "string.quoted.test punctuation.end.test"),
+                (4, ""),
+            ])]
+        );
+    }
+
+    #[test]
+    fn self_include() {
+        assert_eq!(
+            tokenize(&["{ let x }"]),
+            [tokens(&[
+                (0, "meta.block.test"),
+                (2, "meta.block.test meta.let.test keyword.test"),
+                (5, "meta.block.test meta.let.test"),
+                (6, "meta.block.test meta.let.test variable.X.test"),
+                (7, "meta.block.test"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn while_rules() {
+        assert_eq!(
+            tokenize(&["> a", "> b", "c"]),
+            [
+                tokens(&[(0, "markup.quote.test")]),
+                tokens(&[(0, "markup.quote.test")]),
+                tokens(&[(0, "")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn anchor() {
+        // `\G` matches where the begin match ended and nowhere else
+        assert_eq!(
+            tokenize(&["@tag x"]),
+            [tokens(&[
+                (0, "meta.annotation.test entity.name.tag.test"),
+                (4, ""),
+            ])]
+        );
+        assert_eq!(
+            tokenize(&["#title word"]),
+            [tokens(&[
+                (0, "markup.heading.test"),
+                (1, "markup.heading.test entity.name.section.test"),
+                (6, "markup.heading.test"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn capture_patterns() {
+        assert_eq!(
+            tokenize(&["[a 12]"]),
+            [tokens(&[
+                (0, "punctuation.test"),
+                (1, "meta.list.test"),
+                (3, "meta.list.test constant.numeric.test"),
+                (5, "punctuation.test"),
+            ])]
+        );
+    }
+
+    #[test]
+    fn utf16_offsets() {
+        assert_eq!(
+            tokenize(&["ä /* b"]),
+            [tokens(&[(0, ""), (2, "comment.block.test")])]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use super::FontStyle;
+use crate::sys::editor::{self, BuiltinTheme, IStandaloneThemeData, ITokenThemeRule};
+use js_sys::{Array, Object};
+use std::{collections::BTreeMap, error::Error, fmt, str::FromStr};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Error returned when parsing an invalid [`Color`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseColorError {
+    value: String,
+}
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid color {:?}, expected #rgb, #rgba, #rrggbb or #rrggbbaa",
+            self.value
+        )
+    }
+}
+impl Error for ParseColorError {}
+
+/// An RGBA color.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 0xff)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Parse a CSS hex color.
+    ///
+    /// Accepts `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`. The leading `#` is
+    /// optional.
+    pub fn from_hex(hex: &str) -> Result<Self, ParseColorError> {
+        let err = || ParseColorError {
+            value: hex.to_owned(),
+        };
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(err());
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).map_err(|_| err());
+        let short = |i: usize| channel(&digits[i..=i]).map(|v| v * 0x11);
+        let long = |i: usize| channel(&digits[2 * i..2 * i + 2]);
+        match digits.len() {
+            3 => Ok(Self::rgb(short(0)?, short(1)?, short(2)?)),
+            4 => Ok(Self::rgba(short(0)?, short(1)?, short(2)?, short(3)?)),
+            6 => Ok(Self::rgb(long(0)?, long(1)?, long(2)?)),
+            8 => Ok(Self::rgba(long(0)?, long(1)?, long(2)?, long(3)?)),
+            _ => Err(err()),
+        }
+    }
+
+    /// Format the color as `#rrggbb`, or `#rrggbbaa` if it isn't opaque.
+    pub fn to_hex(&self) -> String {
+        if self.a == 0xff {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+str_enum! {
+    /// Well-known ids of workbench colors used by the editor.
+    ///
+    /// Use [`Theme::with_custom_color`] for ids that aren't listed here.
+    pub enum ColorId {
+        Foreground = "foreground",
+        ErrorForeground = "errorForeground",
+        FocusBorder = "focusBorder",
+        ContrastBorder = "contrastBorder",
+        ContrastActiveBorder = "contrastActiveBorder",
+        WidgetShadow = "widget.shadow",
+        EditorBackground = "editor.background",
+        EditorForeground = "editor.foreground",
+        EditorSelectionBackground = "editor.selectionBackground",
+        EditorSelectionForeground = "editor.selectionForeground",
+        EditorInactiveSelectionBackground = "editor.inactiveSelectionBackground",
+        EditorSelectionHighlightBackground = "editor.selectionHighlightBackground",
+        EditorFindMatchBackground = "editor.findMatchBackground",
+        EditorFindMatchHighlightBackground = "editor.findMatchHighlightBackground",
+        EditorHoverHighlightBackground = "editor.hoverHighlightBackground",
+        EditorLineHighlightBackground = "editor.lineHighlightBackground",
+        EditorLineHighlightBorder = "editor.lineHighlightBorder",
+        EditorRangeHighlightBackground = "editor.rangeHighlightBackground",
+        EditorWordHighlightBackground = "editor.wordHighlightBackground",
+        EditorWordHighlightStrongBackground = "editor.wordHighlightStrongBackground",
+        EditorCursorForeground = "editorCursor.foreground",
+        EditorWhitespaceForeground = "editorWhitespace.foreground",
+        EditorIndentGuideBackground = "editorIndentGuide.background",
+        EditorIndentGuideActiveBackground = "editorIndentGuide.activeBackground",
+        EditorLineNumberForeground = "editorLineNumber.foreground",
+        EditorLineNumberActiveForeground = "editorLineNumber.activeForeground",
+        EditorRulerForeground = "editorRuler.foreground",
+        EditorBracketMatchBackground = "editorBracketMatch.background",
+        EditorBracketMatchBorder = "editorBracketMatch.border",
+        EditorGutterBackground = "editorGutter.background",
+        EditorErrorForeground = "editorError.foreground",
+        EditorWarningForeground = "editorWarning.foreground",
+        EditorInfoForeground = "editorInfo.foreground",
+        EditorOverviewRulerBorder = "editorOverviewRuler.border",
+        EditorWidgetBackground = "editorWidget.background",
+        EditorWidgetForeground = "editorWidget.foreground",
+        EditorWidgetBorder = "editorWidget.border",
+        EditorSuggestWidgetBackground = "editorSuggestWidget.background",
+        EditorSuggestWidgetBorder = "editorSuggestWidget.border",
+        EditorSuggestWidgetForeground = "editorSuggestWidget.foreground",
+        EditorSuggestWidgetSelectedBackground = "editorSuggestWidget.selectedBackground",
+        EditorHoverWidgetBackground = "editorHoverWidget.background",
+        EditorHoverWidgetBorder = "editorHoverWidget.border",
+        DiffEditorInsertedTextBackground = "diffEditor.insertedTextBackground",
+        DiffEditorRemovedTextBackground = "diffEditor.removedTextBackground",
+        InputBackground = "input.background",
+        InputForeground = "input.foreground",
+        InputBorder = "input.border",
+        ScrollbarShadow = "scrollbar.shadow",
+        ScrollbarSliderBackground = "scrollbarSlider.background",
+        ScrollbarSliderHoverBackground = "scrollbarSlider.hoverBackground",
+        ScrollbarSliderActiveBackground = "scrollbarSlider.activeBackground",
+        MinimapBackground = "minimap.background",
+    }
+}
+
+/// A rule styling the tokens whose scope starts with `token`.
+/// This is the typed version of [`ITokenThemeRule`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TokenRule {
+    /// Token scope, for example `"comment"` or `"keyword.rust"`.
+    /// The empty string styles all tokens.
+    pub token: String,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    /// Font style of the token.
+    /// [`FontStyle::NONE`] resets the font style inherited from other rules.
+    pub font_style: Option<FontStyle>,
+}
+impl TokenRule {
+    builder_methods! {
+        pub with foreground(Color);
+        pub with background(Color);
+        pub with font_style(FontStyle);
+    }
+
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Convert into [`ITokenThemeRule`].
+    pub fn to_sys_rule(&self) -> ITokenThemeRule {
+        // this helps ensure we don't miss any members
+        let Self {
+            token,
+            foreground,
+            background,
+            font_style,
+        } = self;
+
+        let rule: ITokenThemeRule = Object::new().unchecked_into();
+        rule.set_token(token);
+        rule.set_foreground(foreground.map(|c| token_color(&c)).as_deref());
+        rule.set_background(background.map(|c| token_color(&c)).as_deref());
+        rule.set_font_style(font_style.map(FontStyle::to_value).as_deref());
+        rule
+    }
+}
+
+impl From<&TokenRule> for ITokenThemeRule {
+    fn from(rule: &TokenRule) -> Self {
+        rule.to_sys_rule()
+    }
+}
+
+/// Token rule colors are given without the leading `#` and can't be
+/// transparent.
+fn token_color(color: &Color) -> String {
+    format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// A custom editor theme.
+/// This is the typed version of [`IStandaloneThemeData`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Theme {
+    /// The built-in theme this theme is based on.
+    pub base: BuiltinTheme,
+    /// Whether the rules and colors of `base` apply when they aren't
+    /// overridden by this theme.
+    pub inherit: bool,
+    pub rules: Vec<TokenRule>,
+    /// Workbench colors keyed by their id.
+    pub colors: BTreeMap<String, Color>,
+    /// Colors referenced by the color ids of encoded tokens.
+    pub encoded_tokens_colors: Option<Vec<Color>>,
+}
+impl Theme {
+    builder_methods! {
+        pub with encoded_tokens_colors(Vec<Color>);
+    }
+
+    /// Create a theme inheriting from `base`.
+    pub fn new(base: BuiltinTheme) -> Self {
+        Self {
+            base,
+            inherit: true,
+            rules: Vec::new(),
+            colors: BTreeMap::new(),
+            encoded_tokens_colors: None,
+        }
+    }
+
+    pub fn with_inherit(mut self, inherit: bool) -> Self {
+        self.inherit = inherit;
+        self
+    }
+
+    pub fn with_rule(mut self, rule: TokenRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn with_color(self, id: ColorId, color: Color) -> Self {
+        self.with_custom_color(id.to_value(), color)
+    }
+
+    /// Set a color whose id isn't part of [`ColorId`].
+    pub fn with_custom_color(mut self, id: impl Into<String>, color: Color) -> Self {
+        self.colors.insert(id.into(), color);
+        self
+    }
+
+    /// Convert into [`IStandaloneThemeData`].
+    pub fn to_sys_theme(&self) -> IStandaloneThemeData {
+        // this helps ensure we don't miss any members
+        let Self {
+            base,
+            inherit,
+            rules,
+            colors,
+            encoded_tokens_colors,
+        } = self;
+
+        let theme: IStandaloneThemeData = Object::new().unchecked_into();
+        theme.set_base(*base);
+        theme.set_inherit(*inherit);
+        theme.set_rules(&rules.iter().map(TokenRule::to_sys_rule).collect());
+
+        let js_colors = Object::new();
+        for (id, color) in colors {
+            js_sys::Reflect::set(&js_colors, &id.into(), &color.to_hex().into())
+                .expect("setting key on object must not fail");
+        }
+        theme.set_colors(&js_colors);
+
+        if let Some(encoded_tokens_colors) = encoded_tokens_colors {
+            let js_encoded: Array = encoded_tokens_colors
+                .iter()
+                .map(|color| JsValue::from(color.to_hex()))
+                .collect();
+            theme.set_encoded_tokens_colors(Some(&js_encoded));
+        }
+        theme
+    }
+
+    /// Define the theme as `name`, replacing an existing theme with the same
+    /// name.
+    ///
+    /// Use [`set_global_theme`](super::set_global_theme) to switch to it.
+    pub fn define(&self, name: &str) -> Result<(), JsValue> {
+        editor::define_theme(name, &self.to_sys_theme())
+    }
+}
+
+impl From<&Theme> for IStandaloneThemeData {
+    fn from(theme: &Theme) -> Self {
+        theme.to_sys_theme()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_from_hex() {
+        assert_eq!(Color::from_hex("#fa0"), Ok(Color::rgb(0xff, 0xaa, 0x00)));
+        assert_eq!(
+            Color::from_hex("#fa08"),
+            Ok(Color::rgba(0xff, 0xaa, 0x00, 0x88))
+        );
+        assert_eq!(Color::from_hex("#1e1E2f"), Ok(Color::rgb(0x1e, 0x1e, 0x2f)));
+        assert_eq!(
+            Color::from_hex("1e1e2f80"),
+            Ok(Color::rgba(0x1e, 0x1e, 0x2f, 0x80))
+        );
+    }
+
+    #[test]
+    fn color_from_invalid_hex() {
+        for hex in [
+            "",
+            "#",
+            "#ff",
+            "#fffff",
+            "#fffffffff",
+            "#ggg",
+            "#+1f",
+            "##fff",
+            "#ffé",
+        ] {
+            assert!(Color::from_hex(hex).is_err(), "{:?}", hex);
+        }
+    }
+
+    #[test]
+    fn color_to_hex() {
+        assert_eq!(Color::rgb(0x1e, 0x1e, 0x2f).to_hex(), "#1e1e2f");
+        assert_eq!(Color::rgba(0x1e, 0x1e, 0x2f, 0x80).to_hex(), "#1e1e2f80");
+        let color = Color::rgba(1, 2, 3, 4);
+        assert_eq!(color.to_hex().parse(), Ok(color));
+    }
+
+    #[test]
+    fn token_color_drops_alpha() {
+        assert_eq!(token_color(&Color::rgb(0xab, 0xcd, 0xef)), "abcdef");
+        assert_eq!(token_color(&Color::rgba(0xab, 0xcd, 0xef, 0x80)), "abcdef");
+    }
+}
@@ -0,0 +1,254 @@
+use super::{set_global_theme, Theme};
+use crate::sys::editor::BuiltinTheme;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt,
+    rc::{Rc, Weak},
+};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::MediaQueryList;
+
+const DARK_COLOR_SCHEME_QUERY: &str = "(prefers-color-scheme: dark)";
+
+type Listener = Rc<dyn Fn(&str)>;
+
+thread_local! {
+    static GLOBAL: ThemeRegistry = ThemeRegistry::new();
+}
+
+/// Listens to the `prefers-color-scheme` media query.
+struct ColorSchemeFollower {
+    light: String,
+    dark: String,
+    query: MediaQueryList,
+    on_change: Closure<dyn FnMut()>,
+}
+impl ColorSchemeFollower {
+    fn theme(&self) -> &str {
+        if self.query.matches() {
+            &self.dark
+        } else {
+            &self.light
+        }
+    }
+}
+impl Drop for ColorSchemeFollower {
+    fn drop(&mut self) {
+        let _ = self
+            .query
+            .remove_event_listener_with_callback("change", self.on_change.as_ref().unchecked_ref());
+    }
+}
+
+struct Inner {
+    themes: BTreeMap<String, Theme>,
+    current: String,
+    follower: Option<ColorSchemeFollower>,
+    listeners: Vec<(usize, Listener)>,
+    next_listener_id: usize,
+}
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            themes: BTreeMap::new(),
+            // the theme Monaco starts with
+            current: BuiltinTheme::Vs.to_value().to_owned(),
+            follower: None,
+            listeners: Vec::new(),
+            next_listener_id: 0,
+        }
+    }
+}
+
+/// Keeps track of the defined themes and the theme the editors use.
+///
+/// Monaco doesn't report theme changes, so the theme should only be changed
+/// through the registry for [`current`](Self::current) to be accurate.
+///
+/// Cloning the registry returns a handle to the same registry.
+#[derive(Clone, Default)]
+pub struct ThemeRegistry {
+    inner: Rc<RefCell<Inner>>,
+}
+impl ThemeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The registry used by the editor components to apply their `theme`.
+    ///
+    /// Use it instead of a registry of your own to keep
+    /// [`current`](Self::current) in sync with the components.
+    pub fn global() -> Self {
+        GLOBAL.with(Self::clone)
+    }
+
+    /// Define `theme` as `name`, replacing an existing theme with the same
+    /// name.
+    ///
+    /// If `name` is the current theme, editors are updated immediately.
+    pub fn define(&self, name: &str, theme: Theme) -> Result<(), JsValue> {
+        theme.define(name)?;
+        self.inner
+            .borrow_mut()
+            .themes
+            .insert(name.to_owned(), theme);
+        Ok(())
+    }
+
+    /// Get a theme defined with [`define`](Self::define).
+    pub fn get(&self, name: &str) -> Option<Theme> {
+        self.inner.borrow().themes.get(name).cloned()
+    }
+
+    /// Names of the themes defined with [`define`](Self::define).
+    pub fn names(&self) -> Vec<String> {
+        self.inner.borrow().themes.keys().cloned().collect()
+    }
+
+    /// Name of the current theme.
+    pub fn current(&self) -> String {
+        self.inner.borrow().current.clone()
+    }
+
+    /// The built-in theme the current theme is based on.
+    ///
+    /// Returns `None` if the current theme wasn't defined with this registry.
+    pub fn current_base(&self) -> Option<BuiltinTheme> {
+        let inner = self.inner.borrow();
+        BuiltinTheme::from_value(&inner.current)
+            .or_else(|| inner.themes.get(&inner.current).map(|theme| theme.base))
+    }
+
+    /// Switch to the theme `name`.
+    ///
+    /// This stops following the color scheme, see
+    /// [`follow_color_scheme`](Self::follow_color_scheme).
+    pub fn set_theme(&self, name: &str) {
+        let follower = self.inner.borrow_mut().follower.take();
+        drop(follower);
+        switch_theme(&self.inner, name);
+    }
+
+    /// Switch to a built-in theme.
+    ///
+    /// This stops following the color scheme, see
+    /// [`follow_color_scheme`](Self::follow_color_scheme).
+    pub fn set_builtin_theme(&self, theme: BuiltinTheme) {
+        self.set_theme(theme.to_value())
+    }
+
+    /// Switch between the themes `light` and `dark` depending on the
+    /// `prefers-color-scheme` media query of the browser.
+    ///
+    /// The matching theme is applied immediately. Following continues until
+    /// [`set_theme`](Self::set_theme) is called or the last handle to the
+    /// registry is dropped.
+    pub fn follow_color_scheme(&self, light: &str, dark: &str) -> Result<(), JsValue> {
+        let window =
+            web_sys::window().ok_or_else(|| js_sys::Error::new("no global window exists"))?;
+        let query = window
+            .match_media(DARK_COLOR_SCHEME_QUERY)?
+            .ok_or_else(|| js_sys::Error::new("media queries aren't supported"))?;
+
+        let on_change = {
+            let weak = Rc::downgrade(&self.inner);
+            Closure::wrap(Box::new(move || on_color_scheme_change(&weak)) as Box<dyn FnMut()>)
+        };
+        query.add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())?;
+        let follower = ColorSchemeFollower {
+            light: light.to_owned(),
+            dark: dark.to_owned(),
+            query,
+            on_change,
+        };
+        let theme = follower.theme().to_owned();
+
+        let previous = self.inner.borrow_mut().follower.replace(follower);
+        // remove the previous event listener outside of the borrow
+        drop(previous);
+        switch_theme(&self.inner, &theme);
+        Ok(())
+    }
+
+    /// Whether the theme follows the color scheme of the browser.
+    pub fn is_following_color_scheme(&self) -> bool {
+        self.inner.borrow().follower.is_some()
+    }
+
+    /// Call `listener` with the name of the new theme whenever the current
+    /// theme changes.
+    ///
+    /// The listener is removed when the returned value is dropped.
+    pub fn on_did_change_theme(&self, listener: impl Fn(&str) + 'static) -> ThemeListener {
+        let mut inner = self.inner.borrow_mut();
+        let id = inner.next_listener_id;
+        inner.next_listener_id += 1;
+        inner.listeners.push((id, Rc::new(listener)));
+        ThemeListener {
+            registry: Rc::downgrade(&self.inner),
+            id,
+        }
+    }
+}
+impl fmt::Debug for ThemeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+        let color_scheme = inner
+            .follower
+            .as_ref()
+            .map(|follower| (&follower.light, &follower.dark));
+        f.debug_struct("ThemeRegistry")
+            .field("themes", &inner.themes.keys().collect::<Vec<_>>())
+            .field("current", &inner.current)
+            .field("color_scheme", &color_scheme)
+            .finish()
+    }
+}
+
+/// Listener registered with [`ThemeRegistry::on_did_change_theme`].
+#[must_use = "immediately removed when dropped"]
+#[derive(Debug)]
+pub struct ThemeListener {
+    registry: Weak<RefCell<Inner>>,
+    id: usize,
+}
+impl Drop for ThemeListener {
+    fn drop(&mut self) {
+        if let Some(inner) = self.registry.upgrade() {
+            inner
+                .borrow_mut()
+                .listeners
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+fn on_color_scheme_change(inner: &Weak<RefCell<Inner>>) {
+    let inner = match inner.upgrade() {
+        Some(inner) => inner,
+        None => return,
+    };
+    let theme = match &inner.borrow().follower {
+        Some(follower) => follower.theme().to_owned(),
+        None => return,
+    };
+    switch_theme(&inner, &theme);
+}
+
+/// Apply the theme and notify the listeners if it changed.
+fn switch_theme(inner: &Rc<RefCell<Inner>>, name: &str) {
+    set_global_theme(name);
+    let listeners: Vec<_> = {
+        let mut inner = inner.borrow_mut();
+        if inner.current == name {
+            return;
+        }
+        inner.current = name.to_owned();
+        inner.listeners.iter().map(|(_, l)| Rc::clone(l)).collect()
+    };
+    for listener in listeners {
+        listener(name);
+    }
+}
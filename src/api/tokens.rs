@@ -0,0 +1,571 @@
+use super::DisposableClosures;
+use crate::sys::languages::{self, IEncodedLineTokens, ILineTokens, IState, IToken};
+use js_sys::{Array, Function, Object, Reflect, Uint32Array};
+use std::{
+    any::Any,
+    cell::{OnceCell, RefCell},
+    ops::{BitOr, BitOrAssign},
+    rc::{Rc, Weak},
+};
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+/// A token produced by a [`Tokenizer`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Token {
+    /// Offset of the first character of the token in the line, in UTF-16 code
+    /// units.
+    ///
+    /// This is not the byte offset into the `&str` passed to the tokenizer.
+    /// Use [`utf16_offset`] to convert between the two.
+    pub start_index: u32,
+    /// Space separated list of scopes, used to look up the token's style in
+    /// the theme.
+    pub scopes: String,
+}
+impl Token {
+    pub fn new(start_index: u32, scopes: impl Into<String>) -> Self {
+        Self {
+            start_index,
+            scopes: scopes.into(),
+        }
+    }
+
+    /// Convert into [`IToken`].
+    pub fn to_sys_token(&self) -> IToken {
+        let token: IToken = Object::new().unchecked_into();
+        token.set_start_index(self.start_index.into());
+        token.set_scopes(&self.scopes);
+        token
+    }
+}
+
+impl From<&Token> for IToken {
+    fn from(token: &Token) -> Self {
+        token.to_sys_token()
+    }
+}
+
+/// A line tokenizer implemented in Rust.
+///
+/// The state is an ordinary Rust value. Monaco keeps one state per line and
+/// only asks to re-tokenize the following lines when the end state of a line
+/// changes, so `State` should be small and its [`Eq`] implementation cheap.
+///
+/// Register it using [`set_tokens_provider`].
+pub trait Tokenizer: 'static {
+    type State: Clone + Eq + 'static;
+
+    /// The state passed in to tokenize the first line.
+    fn initial_state(&self) -> Self::State;
+
+    /// Tokenize a line given the state at the beginning of the line.
+    ///
+    /// Returns the tokens on the line together with the state at the end of
+    /// the line.
+    fn tokenize_line(&self, line: &str, state: &Self::State) -> (Vec<Token>, Self::State);
+}
+
+/// The standard token type of an [`EncodedToken`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum StandardTokenType {
+    #[default]
+    Other = 0,
+    Comment = 1,
+    String = 2,
+    RegEx = 3,
+}
+impl StandardTokenType {
+    fn from_bits(bits: u32) -> Self {
+        match bits & 0b11 {
+            1 => Self::Comment,
+            2 => Self::String,
+            3 => Self::RegEx,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// A set of font style flags.
+///
+/// Flags can be combined using `|`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FontStyle(u8);
+impl FontStyle {
+    pub const BOLD: Self = Self(2);
+    pub const ITALIC: Self = Self(1);
+    pub const NONE: Self = Self(0);
+    pub const STRIKETHROUGH: Self = Self(8);
+    pub const UNDERLINE: Self = Self(4);
+
+    /// Create the font style from its raw bits, ignoring unknown bits.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & 0b1111)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether all flags of `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Parse the font style from its theme value, a space separated list of
+    /// `italic`, `bold`, `underline` and `strikethrough`.
+    ///
+    /// Returns `None` if the value contains an unknown style.
+    pub fn from_value(val: &str) -> Option<Self> {
+        val.split_whitespace().try_fold(Self::NONE, |style, name| {
+            let flag = match name {
+                "italic" => Self::ITALIC,
+                "bold" => Self::BOLD,
+                "underline" => Self::UNDERLINE,
+                "strikethrough" => Self::STRIKETHROUGH,
+                _ => return None,
+            };
+            Some(style | flag)
+        })
+    }
+
+    /// Get the theme value of the font style.
+    pub fn to_value(self) -> String {
+        [
+            (Self::ITALIC, "italic"),
+            (Self::BOLD, "bold"),
+            (Self::UNDERLINE, "underline"),
+            (Self::STRIKETHROUGH, "strikethrough"),
+        ]
+        .iter()
+        .filter(|(flag, _)| self.contains(*flag))
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+impl BitOr for FontStyle {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+impl BitOrAssign for FontStyle {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Metadata of an [`EncodedToken`].
+///
+/// See [`IEncodedLineTokens::tokens`] for a description of the binary format.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TokenMetadata {
+    /// Encoded id of the language, see [`encoded_language_id`].
+    pub language_id: u8,
+    pub token_type: StandardTokenType,
+    pub font_style: FontStyle,
+    /// Index into the color map of the theme.
+    /// 0 means no color, 1 is the default foreground color.
+    pub foreground: u16,
+    /// Index into the color map of the theme.
+    /// 0 means no color, 2 is the default background color.
+    pub background: u16,
+}
+impl TokenMetadata {
+    const BACKGROUND_OFFSET: u32 = 23;
+    const COLOR_MASK: u32 = 0b1_1111_1111;
+    const FONT_STYLE_OFFSET: u32 = 10;
+    const FOREGROUND_OFFSET: u32 = 14;
+    const TOKEN_TYPE_OFFSET: u32 = 8;
+
+    /// Encode the metadata into its binary format.
+    ///
+    /// Color ids are truncated to 9 bits.
+    pub fn to_bits(&self) -> u32 {
+        u32::from(self.language_id)
+            | (self.token_type as u32) << Self::TOKEN_TYPE_OFFSET
+            | u32::from(self.font_style.bits()) << Self::FONT_STYLE_OFFSET
+            | (u32::from(self.foreground) & Self::COLOR_MASK) << Self::FOREGROUND_OFFSET
+            | (u32::from(self.background) & Self::COLOR_MASK) << Self::BACKGROUND_OFFSET
+    }
+
+    /// Decode the metadata from its binary format.
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            language_id: (bits & 0xff) as u8,
+            token_type: StandardTokenType::from_bits(bits >> Self::TOKEN_TYPE_OFFSET),
+            font_style: FontStyle::from_bits((bits >> Self::FONT_STYLE_OFFSET) as u8),
+            foreground: ((bits >> Self::FOREGROUND_OFFSET) & Self::COLOR_MASK) as u16,
+            background: ((bits >> Self::BACKGROUND_OFFSET) & Self::COLOR_MASK) as u16,
+        }
+    }
+}
+
+impl From<TokenMetadata> for u32 {
+    fn from(metadata: TokenMetadata) -> Self {
+        metadata.to_bits()
+    }
+}
+
+impl From<u32> for TokenMetadata {
+    fn from(bits: u32) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+/// A token produced by an [`EncodedTokenizer`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EncodedToken {
+    /// Offset of the first character of the token in the line, in UTF-16 code
+    /// units.
+    ///
+    /// This is not the byte offset into the `&str` passed to the tokenizer.
+    /// Use [`utf16_offset`] to convert between the two.
+    pub start_index: u32,
+    pub metadata: TokenMetadata,
+}
+impl EncodedToken {
+    pub fn new(start_index: u32, metadata: TokenMetadata) -> Self {
+        Self {
+            start_index,
+            metadata,
+        }
+    }
+}
+
+/// A line tokenizer which resolves the styling of its tokens itself.
+///
+/// This is the encoded version of [`Tokenizer`].
+/// Register it using [`set_encoded_tokens_provider`].
+pub trait EncodedTokenizer: 'static {
+    type State: Clone + Eq + 'static;
+
+    /// The state passed in to tokenize the first line.
+    fn initial_state(&self) -> Self::State;
+
+    /// Tokenize a line given the state at the beginning of the line.
+    ///
+    /// Returns the tokens on the line together with the state at the end of
+    /// the line.
+    fn tokenize_line_encoded(
+        &self,
+        line: &str,
+        state: &Self::State,
+    ) -> (Vec<EncodedToken>, Self::State);
+}
+
+/// Convert the byte offset `index` into `line` to the UTF-16 offset used for
+/// token start indices.
+///
+/// # Panics
+///
+/// Panics if `index` isn't on a character boundary of `line`.
+pub fn utf16_offset(line: &str, index: usize) -> u32 {
+    line[..index].encode_utf16().count() as u32
+}
+
+/// Get the encoded id of a language for use in [`TokenMetadata`].
+pub fn encoded_language_id(language_id: &str) -> u8 {
+    languages::get_encoded_language_id(language_id) as u8
+}
+
+#[wasm_bindgen]
+extern "C" {
+    // declared here because older `js-sys` versions don't have it
+    type FinalizationRegistry;
+    #[wasm_bindgen(catch, constructor)]
+    fn new(cleanup: &Function) -> Result<FinalizationRegistry, JsValue>;
+    #[wasm_bindgen(method)]
+    fn register(this: &FinalizationRegistry, target: &JsValue, handle: u32);
+
+    /// A function taking a state handle as its first argument.
+    #[wasm_bindgen(extends = Function)]
+    type StateFunction;
+    #[wasm_bindgen(method, js_name = "bind")]
+    fn bind_handle(this: &StateFunction, context: &JsValue, handle: u32) -> Function;
+}
+
+/// The Rust states of a tokens provider.
+///
+/// Monaco gets [`IState`] objects which only hold a handle into the table.
+/// Their `clone` and `equals` methods are functions shared by all states,
+/// bound to the handle, so tokenizing a line doesn't create any closures.
+/// A state is removed from the table once its object is garbage collected.
+/// Without `FinalizationRegistry` support the states are never removed.
+struct StateTable<S> {
+    states: RefCell<Vec<Option<Rc<S>>>>,
+    free: RefCell<Vec<u32>>,
+    /// Identifies the states of this table.
+    id: Object,
+    /// The shared `clone` and `equals` functions.
+    functions: OnceCell<(StateFunction, StateFunction)>,
+    registry: OnceCell<Option<FinalizationRegistry>>,
+}
+impl<S: Clone + Eq + 'static> StateTable<S> {
+    fn new() -> Rc<Self> {
+        let table = Rc::new(Self {
+            states: RefCell::default(),
+            free: RefCell::default(),
+            id: Object::new(),
+            functions: OnceCell::new(),
+            registry: OnceCell::new(),
+        });
+
+        // the functions outlive the provider, as Monaco may still hold on to
+        // states. There's only one set per provider.
+        let weak = Rc::downgrade(&table);
+        let clone = Closure::<dyn Fn(u32) -> JsValue>::new({
+            let weak = Weak::clone(&weak);
+            move |handle| {
+                let table = match weak.upgrade() {
+                    Some(table) => table,
+                    None => return JsValue::UNDEFINED,
+                };
+                match table.get(handle) {
+                    Some(state) => table.wrap(Rc::new(S::clone(&state))).into(),
+                    None => JsValue::UNDEFINED,
+                }
+            }
+        });
+        let equals = Closure::<dyn Fn(u32, JsValue) -> bool>::new({
+            let weak = Weak::clone(&weak);
+            move |handle, other: JsValue| {
+                weak.upgrade().is_some_and(|table| {
+                    match (table.get(handle), table.unwrap(&other)) {
+                        (Some(state), Some(other)) => state == other,
+                        _ => false,
+                    }
+                })
+            }
+        });
+        let cleanup = Closure::<dyn Fn(u32)>::new(move |handle| {
+            if let Some(table) = weak.upgrade() {
+                table.remove(handle);
+            }
+        });
+
+        let _ = table.functions.set((
+            clone.into_js_value().unchecked_into(),
+            equals.into_js_value().unchecked_into(),
+        ));
+        let _ = table
+            .registry
+            .set(FinalizationRegistry::new(cleanup.into_js_value().unchecked_ref()).ok());
+        table
+    }
+
+    fn get(&self, handle: u32) -> Option<Rc<S>> {
+        self.states.borrow().get(handle as usize)?.clone()
+    }
+
+    fn remove(&self, handle: u32) {
+        if let Some(slot) = self.states.borrow_mut().get_mut(handle as usize) {
+            if slot.take().is_some() {
+                self.free.borrow_mut().push(handle);
+            }
+        }
+    }
+
+    /// Wrap a Rust state in an [`IState`] object.
+    fn wrap(&self, state: Rc<S>) -> IState {
+        let handle = match self.free.borrow_mut().pop() {
+            Some(handle) => {
+                self.states.borrow_mut()[handle as usize] = Some(state);
+                handle
+            }
+            None => {
+                let mut states = self.states.borrow_mut();
+                states.push(Some(state));
+                (states.len() - 1) as u32
+            }
+        };
+
+        let object = Object::new();
+        let (clone, equals) = self.functions.get().expect("set in new");
+        object_set!(object.__rustStates = &self.id);
+        object_set!(object.__rustState = handle);
+        object_set!(object.clone = clone.bind_handle(&JsValue::NULL, handle));
+        object_set!(object.equals = equals.bind_handle(&JsValue::NULL, handle));
+        if let Some(registry) = self.registry.get().and_then(Option::as_ref) {
+            registry.register(&object, handle);
+        }
+        object.unchecked_into()
+    }
+
+    /// Get the Rust state of an [`IState`] created by [`wrap`](Self::wrap).
+    ///
+    /// Returns `None` for any other value, including states created by a
+    /// different tokens provider.
+    fn unwrap(&self, object: &JsValue) -> Option<Rc<S>> {
+        let id = object_get!(try object.__rustStates).ok()?;
+        if !Object::is(&id, &self.id) {
+            return None;
+        }
+        let handle = object_get!(try object.__rustState).ok()?.as_f64()?;
+        self.get(handle as u32)
+    }
+}
+
+type InitialStateFn = dyn FnMut() -> JsValue;
+type TokenizeFn = dyn FnMut(String, JsValue) -> JsValue;
+
+/// Build the JS tokens provider object for a Rust tokenizer.
+///
+/// `tokenize` is stored under `tokenize_key` and returns the line tokens
+/// without the end state, which is filled in here.
+/// The returned closures must be kept alive for as long as the provider is in
+/// use.
+fn build_tokens_provider<S>(
+    initial_state: impl Fn() -> S + 'static,
+    tokenize_key: &str,
+    tokenize: impl Fn(&str, &S) -> (Object, S) + 'static,
+) -> (Object, Vec<Box<dyn Any>>)
+where
+    S: Clone + Eq + 'static,
+{
+    let initial_state = Rc::new(initial_state);
+    let states = StateTable::new();
+
+    let get_initial_state = {
+        let initial_state = Rc::clone(&initial_state);
+        let states = Rc::clone(&states);
+        Closure::wrap(
+            Box::new(move || states.wrap(Rc::new(initial_state())).into()) as Box<InitialStateFn>,
+        )
+    };
+
+    let tokenize = Closure::wrap(Box::new(move |line: String, js_state: JsValue| {
+        // a state from another provider can't be used, so start over instead
+        let state = states
+            .unwrap(&js_state)
+            .unwrap_or_else(|| Rc::new(initial_state()));
+        let (line_tokens, end_state) = tokenize(&line, &state);
+        let end_state = states.wrap(Rc::new(end_state));
+        object_set!(line_tokens.endState = end_state);
+        line_tokens.into()
+    }) as Box<TokenizeFn>);
+
+    let provider = Object::new();
+    object_set!(provider.getInitialState = get_initial_state.as_ref());
+    Reflect::set(&provider, &tokenize_key.into(), tokenize.as_ref())
+        .expect("setting key on object must not fail");
+
+    (
+        provider,
+        vec![Box::new(get_initial_state), Box::new(tokenize)],
+    )
+}
+
+/// Convert a [`Tokenizer`] into a JS [`languages::TokensProvider`].
+///
+/// The returned closures must be kept alive for as long as the provider is in
+/// use.
+pub(crate) fn to_js_tokens_provider(
+    tokenizer: impl Tokenizer,
+) -> (languages::TokensProvider, Vec<Box<dyn Any>>) {
+    let tokenizer = Rc::new(tokenizer);
+    let initial_state = {
+        let tokenizer = Rc::clone(&tokenizer);
+        move || tokenizer.initial_state()
+    };
+    let (provider, closures) =
+        build_tokens_provider(initial_state, "tokenize", move |line, state| {
+            let (tokens, end_state) = tokenizer.tokenize_line(line, state);
+            let line_tokens: ILineTokens = Object::new().unchecked_into();
+            let tokens: Array = tokens.iter().map(Token::to_sys_token).collect();
+            line_tokens.set_tokens(&tokens);
+            (line_tokens.unchecked_into(), end_state)
+        });
+    (provider.unchecked_into(), closures)
+}
+
+/// Convert an [`EncodedTokenizer`] into a JS
+/// [`languages::EncodedTokensProvider`].
+///
+/// The returned closures must be kept alive for as long as the provider is in
+/// use.
+pub(crate) fn to_js_encoded_tokens_provider(
+    tokenizer: impl EncodedTokenizer,
+) -> (languages::EncodedTokensProvider, Vec<Box<dyn Any>>) {
+    let tokenizer = Rc::new(tokenizer);
+    let initial_state = {
+        let tokenizer = Rc::clone(&tokenizer);
+        move || tokenizer.initial_state()
+    };
+    let (provider, closures) =
+        build_tokens_provider(initial_state, "tokenizeEncoded", move |line, state| {
+            let (tokens, end_state) = tokenizer.tokenize_line_encoded(line, state);
+            let data: Vec<u32> = tokens
+                .iter()
+                .flat_map(|token| [token.start_index, token.metadata.to_bits()])
+                .collect();
+            let line_tokens: IEncodedLineTokens = Object::new().unchecked_into();
+            line_tokens.set_tokens(&Uint32Array::from(data.as_slice()));
+            (line_tokens.unchecked_into(), end_state)
+        });
+    (provider.unchecked_into(), closures)
+}
+
+/// Set the tokens provider for a language.
+///
+/// This replaces any tokenizer that was previously set for the language,
+/// including Monarch tokenizers.
+/// The provider is unregistered when the returned value is dropped.
+pub fn set_tokens_provider(language_id: &str, tokenizer: impl Tokenizer) -> DisposableClosures {
+    let (provider, closures) = to_js_tokens_provider(tokenizer);
+    let js_disposable = languages::set_tokens_provider(language_id, &provider);
+    DisposableClosures::new(closures, js_disposable)
+}
+
+/// Set an encoded tokens provider for a language.
+///
+/// This replaces any tokenizer that was previously set for the language,
+/// including Monarch tokenizers.
+/// The provider is unregistered when the returned value is dropped.
+pub fn set_encoded_tokens_provider(
+    language_id: &str,
+    tokenizer: impl EncodedTokenizer,
+) -> DisposableClosures {
+    let (provider, closures) = to_js_encoded_tokens_provider(tokenizer);
+    let js_disposable = languages::set_tokens_provider(language_id, &provider);
+    DisposableClosures::new(closures, js_disposable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn font_style_from_value() {
+        assert_eq!(FontStyle::from_value(""), Some(FontStyle::NONE));
+        assert_eq!(FontStyle::from_value("bold"), Some(FontStyle::BOLD));
+        assert_eq!(
+            FontStyle::from_value(" underline  italic bold "),
+            Some(FontStyle::ITALIC | FontStyle::BOLD | FontStyle::UNDERLINE)
+        );
+        assert_eq!(
+            FontStyle::from_value("strikethrough strikethrough"),
+            Some(FontStyle::STRIKETHROUGH)
+        );
+        assert_eq!(FontStyle::from_value("bold oblique"), None);
+        assert_eq!(FontStyle::from_value("Bold"), None);
+    }
+
+    #[test]
+    fn font_style_to_value() {
+        assert_eq!(FontStyle::NONE.to_value(), "");
+        assert_eq!(
+            (FontStyle::STRIKETHROUGH | FontStyle::ITALIC | FontStyle::BOLD).to_value(),
+            "italic bold strikethrough"
+        );
+        for bits in 0..16 {
+            let style = FontStyle::from_bits(bits);
+            assert_eq!(FontStyle::from_value(&style.to_value()), Some(style));
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use super::{widgets::js_code_editor, CodeEditor};
+use crate::sys::editor::{ICodeEditor, IViewZone, IViewZoneChangeAccessor};
+use js_sys::Object;
+use std::{any::Any, cell::RefCell, fmt, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::HtmlElement;
+
+type PxCallback = Box<dyn FnMut(f64)>;
+
+/// A full horizontal rectangle between lines that pushes the text below it
+/// down. This is the typed version of [`IViewZone`].
+///
+/// Add it using [`CodeEditor::view_zones`].
+pub struct ViewZone {
+    /// The line number after which the zone appears.
+    /// Use 0 to place the zone before the first line.
+    pub after_line_number: u32,
+    /// The column after which the zone appears.
+    /// Defaults to the last column of `after_line_number`.
+    pub after_column: Option<u32>,
+    /// The height of the zone in lines.
+    /// If neither this nor `height_in_px` is set, the zone is one line high.
+    pub height_in_lines: Option<f64>,
+    /// The height of the zone in pixels.
+    /// Takes precedence over `height_in_lines`.
+    pub height_in_px: Option<f64>,
+    /// The editor's scroll width is at least this wide.
+    pub min_width_in_px: Option<f64>,
+    pub dom_node: HtmlElement,
+    /// A dom node that is placed in the margin next to the zone.
+    pub margin_dom_node: Option<HtmlElement>,
+    /// Whether mouse down events on the zone are kept from reaching the
+    /// editor.
+    pub suppress_mouse_down: Option<bool>,
+    /// Called with the top of the zone relative to the editor, taking
+    /// scrolling into account.
+    pub on_dom_node_top: Option<PxCallback>,
+    /// Called with the height of the zone in pixels.
+    pub on_computed_height: Option<PxCallback>,
+}
+impl ViewZone {
+    builder_methods! {
+        pub with after_column(u32);
+        pub with height_in_lines(f64);
+        pub with height_in_px(f64);
+        pub with min_width_in_px(f64);
+        pub with margin_dom_node(HtmlElement);
+        pub with suppress_mouse_down(bool);
+    }
+
+    pub fn new(after_line_number: u32, dom_node: HtmlElement) -> Self {
+        Self {
+            after_line_number,
+            after_column: None,
+            height_in_lines: None,
+            height_in_px: None,
+            min_width_in_px: None,
+            dom_node,
+            margin_dom_node: None,
+            suppress_mouse_down: None,
+            on_dom_node_top: None,
+            on_computed_height: None,
+        }
+    }
+
+    pub fn with_on_dom_node_top(mut self, callback: impl FnMut(f64) + 'static) -> Self {
+        self.on_dom_node_top = Some(Box::new(callback));
+        self
+    }
+
+    pub fn with_on_computed_height(mut self, callback: impl FnMut(f64) + 'static) -> Self {
+        self.on_computed_height = Some(Box::new(callback));
+        self
+    }
+
+    /// Convert into [`IViewZone`].
+    ///
+    /// The returned closures must be kept alive for as long as the zone is
+    /// used.
+    fn into_sys_zone(self) -> (IViewZone, Vec<Box<dyn Any>>) {
+        // this helps ensure we don't miss any members
+        let Self {
+            after_line_number,
+            after_column,
+            height_in_lines,
+            height_in_px,
+            min_width_in_px,
+            dom_node,
+            margin_dom_node,
+            suppress_mouse_down,
+            on_dom_node_top,
+            on_computed_height,
+        } = self;
+
+        let zone: IViewZone = Object::new().unchecked_into();
+        zone.set_after_line_number(after_line_number.into());
+        zone.set_after_column(after_column.map(Into::into));
+        zone.set_height_in_lines(height_in_lines);
+        zone.set_height_in_px(height_in_px);
+        zone.set_min_width_in_px(min_width_in_px);
+        zone.set_dom_node(&dom_node);
+        zone.set_margin_dom_node(margin_dom_node.as_ref());
+        zone.set_suppress_mouse_down(suppress_mouse_down);
+
+        let mut closures: Vec<Box<dyn Any>> = Vec::new();
+        if let Some(callback) = on_dom_node_top {
+            let closure = Closure::wrap(callback);
+            zone.set_on_dom_node_top(Some(closure.as_ref().unchecked_ref()));
+            closures.push(Box::new(closure));
+        }
+        if let Some(callback) = on_computed_height {
+            let closure = Closure::wrap(callback);
+            zone.set_on_computed_height(Some(closure.as_ref().unchecked_ref()));
+            closures.push(Box::new(closure));
+        }
+        (zone, closures)
+    }
+}
+impl fmt::Debug for ViewZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewZone")
+            .field("after_line_number", &self.after_line_number)
+            .field("after_column", &self.after_column)
+            .field("height_in_lines", &self.height_in_lines)
+            .field("height_in_px", &self.height_in_px)
+            .field("min_width_in_px", &self.min_width_in_px)
+            .field("dom_node", &self.dom_node)
+            .field("margin_dom_node", &self.margin_dom_node)
+            .field("suppress_mouse_down", &self.suppress_mouse_down)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Run `f` with Monaco's view zone accessor.
+fn change_view_zones(js_editor: &ICodeEditor, f: impl FnOnce(&IViewZoneChangeAccessor) + 'static) {
+    let mut f = Some(f);
+    let callback = Closure::wrap(Box::new(move |accessor: IViewZoneChangeAccessor| {
+        if let Some(f) = f.take() {
+            f(&accessor);
+        }
+    }) as Box<dyn FnMut(IViewZoneChangeAccessor)>);
+    // the callback is called synchronously
+    js_editor.change_view_zones(callback.as_ref().unchecked_ref());
+}
+
+type ZoneId = Rc<RefCell<Option<String>>>;
+
+enum ZoneChange {
+    Add(IViewZone, ZoneId),
+    Layout(ZoneId),
+}
+
+/// Collects the changes made in [`CodeEditor::view_zones`].
+///
+/// The changes are applied together once the callback returns.
+pub struct ViewZoneAccessor {
+    js_editor: ICodeEditor,
+    changes: Vec<ZoneChange>,
+}
+impl ViewZoneAccessor {
+    /// Add a view zone.
+    ///
+    /// The zone is removed when the returned handle is dropped, or when the
+    /// editor's model changes.
+    pub fn add_zone(&mut self, zone: ViewZone) -> ViewZoneHandle {
+        let (js_zone, closures) = zone.into_sys_zone();
+        let id = ZoneId::default();
+        let zone: &JsValue = js_zone.as_ref();
+        self.changes.push(ZoneChange::Add(
+            zone.clone().unchecked_into(),
+            Rc::clone(&id),
+        ));
+        ViewZoneHandle {
+            js_editor: self.js_editor.clone().unchecked_into(),
+            js_zone,
+            id,
+            _closures: closures,
+        }
+    }
+
+    /// Make the editor read the position of a zone again.
+    pub fn layout_zone(&mut self, zone: &ViewZoneHandle) {
+        self.changes.push(ZoneChange::Layout(Rc::clone(&zone.id)));
+    }
+}
+impl fmt::Debug for ViewZoneAccessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewZoneAccessor")
+            .field("changes", &self.changes.len())
+            .finish()
+    }
+}
+
+impl CodeEditor {
+    /// Add or lay out view zones.
+    ///
+    /// The changes made with the accessor are applied together after `f`
+    /// returns, so the editor only lays out once.
+    pub fn view_zones<T>(&self, f: impl FnOnce(&mut ViewZoneAccessor) -> T) -> T {
+        let mut accessor = ViewZoneAccessor {
+            js_editor: js_code_editor(self),
+            changes: Vec::new(),
+        };
+        let value = f(&mut accessor);
+        let ViewZoneAccessor { js_editor, changes } = accessor;
+        if !changes.is_empty() {
+            change_view_zones(&js_editor, move |accessor| {
+                for change in changes {
+                    match change {
+                        ZoneChange::Add(zone, id) => {
+                            id.replace(Some(accessor.add_zone(&zone)));
+                        }
+                        ZoneChange::Layout(id) => {
+                            if let Some(id) = &*id.borrow() {
+                                accessor.layout_zone(id);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        value
+    }
+}
+
+/// A [`ViewZone`] added with [`CodeEditor::view_zones`].
+///
+/// The zone is removed from the editor when dropped.
+#[must_use = "zone is removed when dropped"]
+pub struct ViewZoneHandle {
+    js_editor: ICodeEditor,
+    js_zone: IViewZone,
+    id: ZoneId,
+    _closures: Vec<Box<dyn Any>>,
+}
+impl ViewZoneHandle {
+    /// The id Monaco assigned to the zone.
+    pub fn id(&self) -> Option<String> {
+        self.id.borrow().clone()
+    }
+
+    /// Change the height of the zone to `lines` lines.
+    pub fn set_height_in_lines(&self, lines: f64) {
+        self.js_zone.set_height_in_px(None);
+        self.js_zone.set_height_in_lines(Some(lines));
+        self.layout();
+    }
+
+    /// Change the height of the zone to `px` pixels.
+    pub fn set_height_in_px(&self, px: f64) {
+        self.js_zone.set_height_in_lines(None);
+        self.js_zone.set_height_in_px(Some(px));
+        self.layout();
+    }
+
+    /// Move the zone after `line_number` and `column`.
+    pub fn set_after(&self, line_number: u32, column: Option<u32>) {
+        self.js_zone.set_after_line_number(line_number.into());
+        self.js_zone.set_after_column(column.map(Into::into));
+        self.layout();
+    }
+
+    fn layout(&self) {
+        let id = Rc::clone(&self.id);
+        change_view_zones(&self.js_editor, move |accessor| {
+            if let Some(id) = &*id.borrow() {
+                accessor.layout_zone(id);
+            }
+        });
+    }
+}
+impl Drop for ViewZoneHandle {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            change_view_zones(&self.js_editor, move |accessor| accessor.remove_zone(&id));
+        }
+    }
+}
+impl AsRef<IViewZone> for ViewZoneHandle {
+    fn as_ref(&self) -> &IViewZone {
+        &self.js_zone
+    }
+}
+impl fmt::Debug for ViewZoneHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewZoneHandle")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,772 @@
+use super::{Color, FontStyle, Theme, TokenRule};
+use crate::sys::editor::BuiltinTheme;
+use serde::Deserialize;
+use std::{collections::BTreeMap, error::Error, fmt};
+
+/// Error returned when loading a [`VsCodeTheme`].
+#[derive(Debug)]
+pub enum VsCodeThemeError {
+    /// The theme isn't valid JSON or doesn't have the expected shape.
+    Json(serde_json::Error),
+    /// An included theme couldn't be loaded.
+    MissingInclude(String),
+    /// A theme includes itself, directly or through other themes.
+    IncludeCycle(String),
+}
+impl fmt::Display for VsCodeThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid theme: {}", err),
+            Self::MissingInclude(path) => write!(f, "failed to load included theme {:?}", path),
+            Self::IncludeCycle(path) => write!(f, "theme {:?} includes itself", path),
+        }
+    }
+}
+impl Error for VsCodeThemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+impl From<serde_json::Error> for VsCodeThemeError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// One or more TextMate scope selectors.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum VsCodeScopes {
+    /// Comma separated list of selectors.
+    One(String),
+    Many(Vec<String>),
+}
+impl VsCodeScopes {
+    /// Iterate over the individual selectors.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        let selectors: Vec<&str> = match self {
+            Self::One(selectors) => selectors.split(',').collect(),
+            Self::Many(selectors) => selectors.iter().flat_map(|s| s.split(',')).collect(),
+        };
+        selectors
+            .into_iter()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+}
+
+/// Style settings of a [`VsCodeTokenColor`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VsCodeTokenSettings {
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    /// Space separated list of `italic`, `bold`, `underline` and
+    /// `strikethrough`.
+    pub font_style: Option<String>,
+}
+
+/// An entry of the `tokenColors` of a VS Code theme.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct VsCodeTokenColor {
+    pub name: Option<String>,
+    /// The rule applies to all tokens if there is no scope.
+    pub scope: Option<VsCodeScopes>,
+    #[serde(default)]
+    pub settings: VsCodeTokenSettings,
+}
+
+/// A value of the `semanticTokenColors` of a VS Code theme.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum VsCodeSemanticTokenStyle {
+    Foreground(String),
+    #[serde(rename_all = "camelCase")]
+    Style {
+        foreground: Option<String>,
+        font_style: Option<String>,
+        bold: Option<bool>,
+        italic: Option<bool>,
+        underline: Option<bool>,
+        strikethrough: Option<bool>,
+    },
+}
+
+/// A VS Code color theme as found in the `themes` folder of an extension.
+///
+/// Use [`VsCodeTheme::to_theme`] to convert it into a [`Theme`] which can be
+/// defined in Monaco.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VsCodeTheme {
+    pub name: Option<String>,
+    /// `"light"`, `"dark"`, `"hc"` or `"hcLight"`.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// Path of a theme this theme extends.
+    pub include: Option<String>,
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+    #[serde(default)]
+    pub token_colors: Vec<VsCodeTokenColor>,
+    #[serde(default)]
+    pub semantic_token_colors: BTreeMap<String, VsCodeSemanticTokenStyle>,
+}
+impl VsCodeTheme {
+    /// Parse a VS Code theme.
+    ///
+    /// Like VS Code, this accepts comments and trailing commas.
+    pub fn from_json(json: &str) -> Result<Self, VsCodeThemeError> {
+        Ok(serde_json::from_str(&strip_jsonc(json))?)
+    }
+
+    /// Merge the themes in the `include` chain into this theme.
+    ///
+    /// `load` is called with the path of each included theme and returns its
+    /// JSON. Paths are passed on as they appear in the theme, so they're
+    /// relative to the including theme.
+    pub fn resolve_includes(
+        mut self,
+        mut load: impl FnMut(&str) -> Option<String>,
+    ) -> Result<Self, VsCodeThemeError> {
+        let mut seen = Vec::new();
+        while let Some(path) = self.include.take() {
+            if seen.contains(&path) {
+                return Err(VsCodeThemeError::IncludeCycle(path));
+            }
+            let json = load(&path).ok_or_else(|| VsCodeThemeError::MissingInclude(path.clone()))?;
+            let parent = Self::from_json(&json)?;
+            seen.push(path);
+            self = parent.extended_by(self);
+        }
+        Ok(self)
+    }
+
+    /// Apply `child` on top of this theme.
+    fn extended_by(mut self, child: Self) -> Self {
+        let Self {
+            name,
+            kind,
+            include: _,
+            colors,
+            token_colors,
+            semantic_token_colors,
+        } = child;
+
+        self.name = name.or(self.name);
+        self.kind = kind.or(self.kind);
+        self.colors.extend(colors);
+        // later rules take precedence
+        self.token_colors.extend(token_colors);
+        self.semantic_token_colors.extend(semantic_token_colors);
+        self
+    }
+
+    /// The built-in Monaco theme closest to this theme.
+    ///
+    /// Falls back to the brightness of `editor.background` if the theme
+    /// doesn't specify its type.
+    pub fn base(&self) -> BuiltinTheme {
+        match self.kind.as_deref() {
+            Some("light") | Some("hcLight") => BuiltinTheme::Vs,
+            Some("dark") => BuiltinTheme::VsDark,
+            Some("hc") | Some("hcDark") => BuiltinTheme::HcBlack,
+            _ => match self.color("editor.background") {
+                Some(Color { r, g, b, .. })
+                    if u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114
+                        > 128 * 1000 =>
+                {
+                    BuiltinTheme::Vs
+                }
+                Some(_) => BuiltinTheme::VsDark,
+                None => BuiltinTheme::Vs,
+            },
+        }
+    }
+
+    fn color(&self, id: &str) -> Option<Color> {
+        self.colors.get(id).and_then(|c| Color::from_hex(c).ok())
+    }
+
+    /// Convert into a [`Theme`] using the default [`ScopeMap`].
+    pub fn to_theme(&self) -> Theme {
+        self.to_theme_with(&ScopeMap::default())
+    }
+
+    /// Convert into a [`Theme`].
+    ///
+    /// Every TextMate scope is added as a token rule as is, and additionally
+    /// under the Monaco token name `scopes` maps it to.
+    /// Invalid colors and font styles are ignored, as are semantic token
+    /// selectors using wildcards or language filters.
+    pub fn to_theme_with(&self, scopes: &ScopeMap) -> Theme {
+        let mut theme = Theme::new(self.base());
+
+        for (id, color) in &self.colors {
+            if let Ok(color) = Color::from_hex(color) {
+                theme = theme.with_custom_color(id, color);
+            }
+        }
+
+        // default style for tokens which aren't matched by any rule
+        let mut default_rule = TokenRule::new("");
+        default_rule.foreground = self.color("editor.foreground");
+        default_rule.background = self.color("editor.background");
+        if default_rule != TokenRule::new("") {
+            theme = theme.with_rule(default_rule);
+        }
+
+        for token_color in &self.token_colors {
+            let template = token_rule(
+                token_color.settings.foreground.as_deref(),
+                token_color.settings.background.as_deref(),
+                token_color
+                    .settings
+                    .font_style
+                    .as_deref()
+                    .and_then(FontStyle::from_value),
+            );
+            let selectors = match &token_color.scope {
+                Some(selectors) => selectors,
+                None => {
+                    theme = theme.with_rule(template);
+                    continue;
+                }
+            };
+            for selector in selectors.iter() {
+                // exclusions can't be expressed in Monaco
+                if selector.contains(" -") {
+                    continue;
+                }
+                // Monaco doesn't know the parent scopes of a token, so only the
+                // innermost scope of descendant selectors is used
+                let scope = selector.rsplit(' ').next().unwrap_or(selector);
+                theme = theme.with_rule(TokenRule {
+                    token: scope.to_owned(),
+                    ..template.clone()
+                });
+                if let Some(token) = scopes.map(scope) {
+                    if token != scope {
+                        theme = theme.with_rule(TokenRule {
+                            token: token.to_owned(),
+                            ..template.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        for (selector, style) in &self.semantic_token_colors {
+            if selector.starts_with('*') || selector.contains(':') {
+                continue;
+            }
+            let mut rule = match style {
+                VsCodeSemanticTokenStyle::Foreground(foreground) => {
+                    token_rule(Some(foreground), None, None)
+                }
+                VsCodeSemanticTokenStyle::Style {
+                    foreground,
+                    font_style,
+                    bold,
+                    italic,
+                    underline,
+                    strikethrough,
+                } => {
+                    let mut font_style = font_style.as_deref().and_then(FontStyle::from_value);
+                    for (flag, enabled) in [
+                        (FontStyle::BOLD, bold),
+                        (FontStyle::ITALIC, italic),
+                        (FontStyle::UNDERLINE, underline),
+                        (FontStyle::STRIKETHROUGH, strikethrough),
+                    ] {
+                        if *enabled == Some(true) {
+                            *font_style.get_or_insert(FontStyle::NONE) |= flag;
+                        }
+                    }
+                    token_rule(foreground.as_deref(), None, font_style)
+                }
+            };
+            rule.token = selector.clone();
+            theme = theme.with_rule(rule);
+        }
+
+        theme
+    }
+}
+
+impl From<&VsCodeTheme> for Theme {
+    fn from(theme: &VsCodeTheme) -> Self {
+        theme.to_theme()
+    }
+}
+
+fn token_rule(
+    foreground: Option<&str>,
+    background: Option<&str>,
+    font_style: Option<FontStyle>,
+) -> TokenRule {
+    TokenRule {
+        token: String::new(),
+        foreground: foreground.and_then(|c| Color::from_hex(c).ok()),
+        background: background.and_then(|c| Color::from_hex(c).ok()),
+        font_style,
+    }
+}
+
+/// Table mapping TextMate scopes to the token names used by Monaco's
+/// tokenizers.
+///
+/// A scope is mapped using the longest entry that is a prefix of it, so
+/// `constant.numeric` also maps `constant.numeric.integer.rust`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScopeMap {
+    entries: BTreeMap<String, String>,
+}
+impl ScopeMap {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Map `scope` (and all scopes below it) to `token`.
+    pub fn with_mapping(mut self, scope: impl Into<String>, token: impl Into<String>) -> Self {
+        self.entries.insert(scope.into(), token.into());
+        self
+    }
+
+    /// Get the token name for `scope`.
+    pub fn map(&self, scope: &str) -> Option<&str> {
+        let mut prefix = scope;
+        loop {
+            if let Some(token) = self.entries.get(prefix) {
+                return Some(token);
+            }
+            prefix = &prefix[..prefix.rfind('.')?];
+        }
+    }
+}
+impl Default for ScopeMap {
+    /// Table covering the tokens produced by Monaco's built-in languages.
+    fn default() -> Self {
+        [
+            ("comment", "comment"),
+            ("constant.language", "keyword"),
+            ("constant.numeric", "number"),
+            ("constant.other.color", "number.hex"),
+            ("constant.regexp", "regexp"),
+            ("entity.name.class", "type"),
+            ("entity.name.function", "function"),
+            ("entity.name.tag", "tag"),
+            ("entity.name.type", "type"),
+            ("entity.other.attribute-name", "attribute.name"),
+            ("invalid", "invalid"),
+            ("keyword", "keyword"),
+            ("keyword.operator", "operator"),
+            ("markup.bold", "strong"),
+            ("markup.heading", "keyword"),
+            ("markup.italic", "emphasis"),
+            ("meta.preprocessor", "metatag"),
+            ("punctuation", "delimiter"),
+            ("punctuation.definition.tag", "delimiter.html"),
+            ("storage", "keyword"),
+            ("storage.type", "keyword"),
+            ("string", "string"),
+            ("string.regexp", "regexp"),
+            ("support.class", "type"),
+            ("support.function", "predefined"),
+            ("support.type", "type"),
+            ("support.type.property-name", "attribute.name"),
+            ("variable", "variable"),
+            ("variable.language", "variable.predefined"),
+            ("variable.parameter", "variable.parameter"),
+        ]
+        .iter()
+        .fold(Self::new(), |map, (scope, token)| {
+            map.with_mapping(*scope, *token)
+        })
+    }
+}
+
+/// Remove comments and trailing commas from JSON.
+fn strip_jsonc(input: &str) -> String {
+    #[derive(PartialEq)]
+    enum State {
+        Code,
+        Str,
+        StrEscape,
+        LineComment,
+        BlockComment,
+    }
+
+    // remove comments
+    let mut out = String::with_capacity(input.len());
+    let mut state = State::Code;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match state {
+            State::Code => match (c, chars.peek()) {
+                ('"', _) => {
+                    state = State::Str;
+                    out.push(c);
+                }
+                ('/', Some('/')) => {
+                    chars.next();
+                    state = State::LineComment;
+                }
+                ('/', Some('*')) => {
+                    chars.next();
+                    state = State::BlockComment;
+                }
+                _ => out.push(c),
+            },
+            State::Str => {
+                match c {
+                    '\\' => state = State::StrEscape,
+                    '"' => state = State::Code,
+                    _ => {}
+                }
+                out.push(c);
+            }
+            State::StrEscape => {
+                state = State::Str;
+                out.push(c);
+            }
+            State::LineComment => {
+                if c == '\n' {
+                    state = State::Code;
+                    out.push(c);
+                }
+            }
+            State::BlockComment => {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    state = State::Code;
+                    out.push(' ');
+                }
+            }
+        }
+    }
+
+    // remove trailing commas
+    let mut result = String::with_capacity(out.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in out.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = out[i + 1..].trim_start().chars().next();
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn parse(jsonc: &str) -> Value {
+        serde_json::from_str(&strip_jsonc(jsonc)).unwrap()
+    }
+
+    #[test]
+    fn strip_comments() {
+        let jsonc = r#"{
+            // line comment
+            "a": 1, // trailing line comment
+            /* block
+               comment */ "b": /* inline */ 2
+        }"#;
+        assert_eq!(parse(jsonc), json!({ "a": 1, "b": 2 }));
+    }
+
+    #[test]
+    fn comments_inside_strings_are_kept() {
+        let jsonc = r#"{ "url": "https://example.com", "glob": "src/**/*.rs", "c": "/* x */" }"#;
+        assert_eq!(
+            parse(jsonc),
+            json!({ "url": "https://example.com", "glob": "src/**/*.rs", "c": "/* x */" })
+        );
+    }
+
+    #[test]
+    fn escaped_quotes() {
+        let jsonc = r#"{ "a": "say \"hi\" // not a comment", "b": "\\", /* c */ "c": "\\\"," }"#;
+        assert_eq!(
+            parse(jsonc),
+            json!({ "a": "say \"hi\" // not a comment", "b": "\\", "c": "\\\"," })
+        );
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let jsonc = r#"{
+            "list": [1, 2, 3,],
+            "nested": { "a": [ { "b": 1, }, ], },
+            "string": "a,}",
+            "commented": [1, // comment
+            ],
+        }"#;
+        assert_eq!(
+            parse(jsonc),
+            json!({
+                "list": [1, 2, 3],
+                "nested": { "a": [{ "b": 1 }] },
+                "string": "a,}",
+                "commented": [1],
+            })
+        );
+    }
+
+    #[test]
+    fn unterminated_comment_is_removed() {
+        assert_eq!(strip_jsonc("[1] // end"), "[1] ");
+        assert_eq!(strip_jsonc("[1] /* end"), "[1] ");
+    }
+
+    fn rules<'a>(theme: &'a Theme, token: &str) -> Vec<&'a TokenRule> {
+        theme.rules.iter().filter(|r| r.token == token).collect()
+    }
+
+    #[test]
+    fn scope_mapping() {
+        let theme = VsCodeTheme::from_json(
+            r##"{
+                "type": "dark",
+                "tokenColors": [
+                    { "settings": { "foreground": "#aaaaaa" } },
+                    {
+                        "scope": "constant.numeric.integer, meta.block string.quoted",
+                        "settings": { "foreground": "#ff0000", "fontStyle": "bold" }
+                    },
+                    { "scope": ["source.rust -comment", "keyword.other"], "settings": { "foreground": "#00ff00" } }
+                ]
+            }"##,
+        )
+        .unwrap()
+        .to_theme_with(&ScopeMap::new().with_mapping("constant.numeric", "number"));
+
+        let red = TokenRule::new("")
+            .with_foreground(Color::rgb(0xff, 0, 0))
+            .with_font_style(FontStyle::BOLD);
+        assert_eq!(
+            rules(&theme, ""),
+            [&TokenRule::new("").with_foreground(Color::rgb(0xaa, 0xaa, 0xaa))]
+        );
+        assert_eq!(
+            rules(&theme, "constant.numeric.integer"),
+            [&TokenRule {
+                token: "constant.numeric.integer".to_owned(),
+                ..red.clone()
+            }]
+        );
+        assert_eq!(
+            rules(&theme, "number"),
+            [&TokenRule {
+                token: "number".to_owned(),
+                ..red.clone()
+            }]
+        );
+        // descendant selectors only use the innermost scope
+        assert_eq!(rules(&theme, "string.quoted").len(), 1);
+        assert!(rules(&theme, "meta.block").is_empty());
+        assert!(rules(&theme, "meta.block string.quoted").is_empty());
+        // exclusions are skipped, the other selectors of the entry aren't
+        assert!(theme
+            .rules
+            .iter()
+            .all(|r| !r.token.starts_with("source.rust")));
+        assert_eq!(
+            rules(&theme, "keyword.other"),
+            [&TokenRule::new("keyword.other").with_foreground(Color::rgb(0, 0xff, 0))]
+        );
+    }
+
+    #[test]
+    fn editor_colors() {
+        let theme = VsCodeTheme::from_json(
+            r##"{
+                "colors": {
+                    "editor.background": "#1e1e1e",
+                    "editor.foreground": "#d4d4d4",
+                    "broken": "red"
+                }
+            }"##,
+        )
+        .unwrap()
+        .to_theme();
+
+        assert_eq!(theme.colors.len(), 2);
+        assert_eq!(
+            theme.colors["editor.background"],
+            Color::rgb(0x1e, 0x1e, 0x1e)
+        );
+        assert_eq!(
+            rules(&theme, ""),
+            [&TokenRule::new("")
+                .with_foreground(Color::rgb(0xd4, 0xd4, 0xd4))
+                .with_background(Color::rgb(0x1e, 0x1e, 0x1e))]
+        );
+    }
+
+    #[test]
+    fn semantic_token_rules() {
+        let theme = VsCodeTheme::from_json(
+            r##"{
+                "semanticTokenColors": {
+                    "variable": "#010203",
+                    "function": { "foreground": "#040506", "italic": true, "underline": true },
+                    "type": { "fontStyle": "bold", "strikethrough": true, "italic": false },
+                    "*.declaration": "#ffffff",
+                    "variable:rust": "#ffffff"
+                }
+            }"##,
+        )
+        .unwrap()
+        .to_theme();
+
+        assert_eq!(
+            theme.rules,
+            [
+                TokenRule::new("function")
+                    .with_foreground(Color::rgb(4, 5, 6))
+                    .with_font_style(FontStyle::ITALIC | FontStyle::UNDERLINE),
+                TokenRule::new("type").with_font_style(FontStyle::BOLD | FontStyle::STRIKETHROUGH),
+                TokenRule::new("variable").with_foreground(Color::rgb(1, 2, 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn includes_are_merged() {
+        let files = [
+            (
+                "./base.json",
+                r##"{
+                    "name": "Base",
+                    "type": "light",
+                    "colors": { "a": "#000001", "b": "#000001" },
+                    "tokenColors": [{ "scope": "comment", "settings": { "foreground": "#000001" } }]
+                }"##,
+            ),
+            (
+                "./middle.json",
+                r##"{
+                    "include": "./base.json",
+                    "type": "dark",
+                    "colors": { "b": "#000002", "c": "#000002" },
+                    "tokenColors": [{ "scope": "comment", "settings": { "foreground": "#000002" } }]
+                }"##,
+            ),
+        ];
+        let mut loaded = Vec::new();
+        let theme = VsCodeTheme::from_json(
+            r##"{
+                "include": "./middle.json",
+                "colors": { "c": "#000003" },
+                "tokenColors": [{ "scope": "comment", "settings": { "foreground": "#000003" } }]
+            }"##,
+        )
+        .unwrap()
+        .resolve_includes(|path| {
+            loaded.push(path.to_owned());
+            files
+                .iter()
+                .find(|(name, _)| *name == path)
+                .map(|(_, json)| json.to_string())
+        })
+        .unwrap();
+
+        assert_eq!(loaded, ["./middle.json", "./base.json"]);
+        assert_eq!(theme.include, None);
+        assert_eq!(theme.name.as_deref(), Some("Base"));
+        assert_eq!(theme.kind.as_deref(), Some("dark"));
+        assert_eq!(theme.colors["a"], "#000001");
+        assert_eq!(theme.colors["b"], "#000002");
+        assert_eq!(theme.colors["c"], "#000003");
+        // rules of the including theme come last so they take precedence
+        let foregrounds: Vec<_> = theme
+            .token_colors
+            .iter()
+            .map(|c| c.settings.foreground.as_deref().unwrap())
+            .collect();
+        assert_eq!(foregrounds, ["#000001", "#000002", "#000003"]);
+    }
+
+    #[test]
+    fn include_errors() {
+        let theme = VsCodeTheme::from_json(r#"{ "include": "a.json" }"#).unwrap();
+        let err = theme
+            .clone()
+            .resolve_includes(|path| match path {
+                "a.json" => Some(r#"{ "include": "b.json" }"#.to_owned()),
+                "b.json" => Some(r#"{ "include": "a.json" }"#.to_owned()),
+                _ => None,
+            })
+            .unwrap_err();
+        assert!(matches!(err, VsCodeThemeError::IncludeCycle(path) if path == "a.json"));
+
+        let err = theme.resolve_includes(|_| None).unwrap_err();
+        assert!(matches!(err, VsCodeThemeError::MissingInclude(path) if path == "a.json"));
+    }
+
+    #[test]
+    fn base_theme() {
+        let base = |json: &str| VsCodeTheme::from_json(json).unwrap().base();
+        assert_eq!(base(r#"{ "type": "light" }"#), BuiltinTheme::Vs);
+        assert_eq!(base(r#"{ "type": "hcLight" }"#), BuiltinTheme::Vs);
+        assert_eq!(base(r#"{ "type": "dark" }"#), BuiltinTheme::VsDark);
+        assert_eq!(base(r#"{ "type": "hc" }"#), BuiltinTheme::HcBlack);
+        assert_eq!(base(r#"{ "type": "hcDark" }"#), BuiltinTheme::HcBlack);
+        // without a type the brightness of the background decides
+        assert_eq!(
+            base(r##"{ "colors": { "editor.background": "#1e1e1e" } }"##),
+            BuiltinTheme::VsDark
+        );
+        assert_eq!(
+            base(r##"{ "colors": { "editor.background": "#fffffe" } }"##),
+            BuiltinTheme::Vs
+        );
+        assert_eq!(
+            base(r##"{ "type": "dark", "colors": { "editor.background": "#ffffff" } }"##),
+            BuiltinTheme::VsDark
+        );
+        assert_eq!(base("{}"), BuiltinTheme::Vs);
+    }
+
+    #[test]
+    fn scope_map_uses_longest_prefix() {
+        let map = ScopeMap::new()
+            .with_mapping("constant", "constant")
+            .with_mapping("constant.numeric", "number")
+            .with_mapping("constant.numeric.float", "number.float");
+        assert_eq!(map.map("constant.numeric.float"), Some("number.float"));
+        assert_eq!(map.map("constant.numeric.float.rust"), Some("number.float"));
+        assert_eq!(map.map("constant.numeric.integer"), Some("number"));
+        assert_eq!(map.map("constant.language"), Some("constant"));
+        // prefixes are matched on whole segments
+        assert_eq!(map.map("constants"), None);
+        assert_eq!(map.map("string"), None);
+
+        let map = ScopeMap::default();
+        assert_eq!(map.map("keyword.operator.arithmetic"), Some("operator"));
+        assert_eq!(map.map("keyword.control"), Some("keyword"));
+    }
+}
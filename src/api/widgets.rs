@@ -0,0 +1,327 @@
+use super::CodeEditor;
+use crate::sys::{
+    editor::{
+        ContentWidgetPositionPreference,
+        ICodeEditor,
+        IContentWidget,
+        IContentWidgetPosition,
+        IOverlayWidget,
+        IOverlayWidgetPosition,
+        OverlayWidgetPositionPreference,
+    },
+    IPosition,
+    IRange,
+};
+use js_sys::{Array, Object};
+use std::{any::Any, fmt, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::HtmlElement;
+
+/// Where a [`ContentWidget`] is placed.
+/// This is the typed version of [`IContentWidgetPosition`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContentWidgetPosition {
+    /// Line number of the position, starting at 1.
+    pub line_number: u32,
+    /// Column of the position, starting at 1.
+    pub column: u32,
+    /// Optionally, a range can be provided to further define the position.
+    pub range: Option<IRange>,
+    /// Placement preferences relative to the position, in order of
+    /// preference.
+    pub preference: Vec<ContentWidgetPositionPreference>,
+}
+impl ContentWidgetPosition {
+    builder_methods! {
+        pub with range(IRange);
+    }
+
+    pub fn new(
+        line_number: u32,
+        column: u32,
+        preference: impl IntoIterator<Item = ContentWidgetPositionPreference>,
+    ) -> Self {
+        Self {
+            line_number,
+            column,
+            range: None,
+            preference: preference.into_iter().collect(),
+        }
+    }
+
+    /// Convert into [`IContentWidgetPosition`].
+    pub fn to_sys_position(&self) -> IContentWidgetPosition {
+        // this helps ensure we don't miss any members
+        let Self {
+            line_number,
+            column,
+            range,
+            preference,
+        } = self;
+
+        let js_position: IPosition = Object::new().unchecked_into();
+        object_set!(js_position.lineNumber = *line_number);
+        object_set!(js_position.column = *column);
+
+        let position: IContentWidgetPosition = Object::new().unchecked_into();
+        position.set_position(Some(&js_position));
+        position.set_range(range.as_ref());
+        position.set_preference(
+            &preference
+                .iter()
+                .map(|preference| JsValue::from(preference.to_value()))
+                .collect::<Array>(),
+        );
+        position
+    }
+}
+
+impl From<&ContentWidgetPosition> for IContentWidgetPosition {
+    fn from(position: &ContentWidgetPosition) -> Self {
+        position.to_sys_position()
+    }
+}
+
+/// Rust version of [`IContentWidget`].
+///
+/// A content widget renders inline with the text and is placed near an
+/// editor position. Add it using [`CodeEditor::add_content_widget`].
+pub trait ContentWidget: 'static {
+    /// A unique identifier of the widget.
+    /// Widgets with the same id replace each other.
+    fn id(&self) -> String;
+
+    /// The dom node of the widget.
+    /// This must return the same element every time.
+    fn dom_node(&self) -> HtmlElement;
+
+    /// The placement of the widget.
+    /// If `None` is returned, the widget is placed off screen.
+    ///
+    /// This is called again when [`ContentWidgetHandle::layout`] is called.
+    fn position(&self) -> Option<ContentWidgetPosition>;
+
+    /// Whether the widget may overflow the editor's view dom node.
+    /// This is only read when the widget is added.
+    fn allow_editor_overflow(&self) -> bool {
+        false
+    }
+
+    /// Whether mouse down events on the widget are kept from reaching the
+    /// editor.
+    /// This is only read when the widget is added.
+    fn suppress_mouse_down(&self) -> bool {
+        false
+    }
+}
+
+fn to_js_content_widget<W: ContentWidget>(widget: &Rc<W>) -> (IContentWidget, Vec<Box<dyn Any>>) {
+    let get_id = {
+        let widget = Rc::clone(widget);
+        Closure::wrap(Box::new(move || widget.id()) as Box<dyn FnMut() -> String>)
+    };
+    let get_dom_node = {
+        let widget = Rc::clone(widget);
+        Closure::wrap(Box::new(move || widget.dom_node()) as Box<dyn FnMut() -> HtmlElement>)
+    };
+    let get_position = {
+        let widget = Rc::clone(widget);
+        Closure::wrap(Box::new(move || match widget.position() {
+            Some(position) => position.to_sys_position().into(),
+            None => JsValue::NULL,
+        }) as Box<dyn FnMut() -> JsValue>)
+    };
+
+    let js_widget: IContentWidget = Object::new().unchecked_into();
+    object_set!(js_widget.getId = get_id.as_ref());
+    object_set!(js_widget.getDomNode = get_dom_node.as_ref());
+    object_set!(js_widget.getPosition = get_position.as_ref());
+    js_widget.set_allow_editor_overflow(Some(widget.allow_editor_overflow()));
+    js_widget.set_suppress_mouse_down(Some(widget.suppress_mouse_down()));
+
+    let closures: Vec<Box<dyn Any>> = vec![
+        Box::new(get_id),
+        Box::new(get_dom_node),
+        Box::new(get_position),
+    ];
+    (js_widget, closures)
+}
+
+/// Rust version of [`IOverlayWidget`].
+///
+/// An overlay widget renders on top of the text and is anchored to the
+/// editor instead of a position in the text. Add it using
+/// [`CodeEditor::add_overlay_widget`].
+pub trait OverlayWidget: 'static {
+    /// A unique identifier of the widget.
+    /// Widgets with the same id replace each other.
+    fn id(&self) -> String;
+
+    /// The dom node of the widget.
+    /// This must return the same element every time.
+    fn dom_node(&self) -> HtmlElement;
+
+    /// The placement of the widget.
+    /// If `None` is returned, the widget is responsible for placing itself.
+    ///
+    /// This is called again when [`OverlayWidgetHandle::layout`] is called.
+    fn position(&self) -> Option<OverlayWidgetPositionPreference>;
+}
+
+fn to_js_overlay_widget<W: OverlayWidget>(widget: &Rc<W>) -> (IOverlayWidget, Vec<Box<dyn Any>>) {
+    let get_id = {
+        let widget = Rc::clone(widget);
+        Closure::wrap(Box::new(move || widget.id()) as Box<dyn FnMut() -> String>)
+    };
+    let get_dom_node = {
+        let widget = Rc::clone(widget);
+        Closure::wrap(Box::new(move || widget.dom_node()) as Box<dyn FnMut() -> HtmlElement>)
+    };
+    let get_position = {
+        let widget = Rc::clone(widget);
+        Closure::wrap(Box::new(move || match widget.position() {
+            Some(preference) => {
+                let position: IOverlayWidgetPosition = Object::new().unchecked_into();
+                position.set_preference(Some(preference));
+                position.into()
+            }
+            None => JsValue::NULL,
+        }) as Box<dyn FnMut() -> JsValue>)
+    };
+
+    let js_widget: IOverlayWidget = Object::new().unchecked_into();
+    object_set!(js_widget.getId = get_id.as_ref());
+    object_set!(js_widget.getDomNode = get_dom_node.as_ref());
+    object_set!(js_widget.getPosition = get_position.as_ref());
+
+    let closures: Vec<Box<dyn Any>> = vec![
+        Box::new(get_id),
+        Box::new(get_dom_node),
+        Box::new(get_position),
+    ];
+    (js_widget, closures)
+}
+
+/// Get an owned handle to the underlying editor.
+pub(super) fn js_code_editor(editor: &CodeEditor) -> ICodeEditor {
+    let js_editor: &JsValue = editor.as_ref().as_ref();
+    js_editor.clone().unchecked_into()
+}
+
+impl CodeEditor {
+    /// Add a content widget to the editor.
+    ///
+    /// The widget is removed when the returned handle is dropped.
+    pub fn add_content_widget<W: ContentWidget>(&self, widget: W) -> ContentWidgetHandle<W> {
+        let widget = Rc::new(widget);
+        let (js_widget, closures) = to_js_content_widget(&widget);
+        let js_editor = js_code_editor(self);
+        js_editor.add_content_widget(&js_widget);
+        ContentWidgetHandle {
+            js_editor,
+            js_widget,
+            widget,
+            _closures: closures,
+        }
+    }
+
+    /// Add an overlay widget to the editor.
+    ///
+    /// The widget is removed when the returned handle is dropped.
+    pub fn add_overlay_widget<W: OverlayWidget>(&self, widget: W) -> OverlayWidgetHandle<W> {
+        let widget = Rc::new(widget);
+        let (js_widget, closures) = to_js_overlay_widget(&widget);
+        let js_editor = js_code_editor(self);
+        js_editor.add_overlay_widget(&js_widget);
+        OverlayWidgetHandle {
+            js_editor,
+            js_widget,
+            widget,
+            _closures: closures,
+        }
+    }
+}
+
+/// A [`ContentWidget`] added with [`CodeEditor::add_content_widget`].
+///
+/// The widget is removed from the editor when dropped.
+#[must_use = "widget is removed when dropped"]
+pub struct ContentWidgetHandle<W> {
+    js_editor: ICodeEditor,
+    js_widget: IContentWidget,
+    widget: Rc<W>,
+    _closures: Vec<Box<dyn Any>>,
+}
+impl<W: ContentWidget> ContentWidgetHandle<W> {
+    /// The widget.
+    pub fn widget(&self) -> &W {
+        &self.widget
+    }
+
+    /// Tell the editor to read the position of the widget again.
+    ///
+    /// Call this after the position returned by
+    /// [`ContentWidget::position`] changed.
+    pub fn layout(&self) {
+        self.js_editor.layout_content_widget(&self.js_widget);
+    }
+}
+impl<W> Drop for ContentWidgetHandle<W> {
+    fn drop(&mut self) {
+        self.js_editor.remove_content_widget(&self.js_widget);
+    }
+}
+impl<W> AsRef<IContentWidget> for ContentWidgetHandle<W> {
+    fn as_ref(&self) -> &IContentWidget {
+        &self.js_widget
+    }
+}
+impl<W: fmt::Debug> fmt::Debug for ContentWidgetHandle<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentWidgetHandle")
+            .field("widget", &self.widget)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An [`OverlayWidget`] added with [`CodeEditor::add_overlay_widget`].
+///
+/// The widget is removed from the editor when dropped.
+#[must_use = "widget is removed when dropped"]
+pub struct OverlayWidgetHandle<W> {
+    js_editor: ICodeEditor,
+    js_widget: IOverlayWidget,
+    widget: Rc<W>,
+    _closures: Vec<Box<dyn Any>>,
+}
+impl<W: OverlayWidget> OverlayWidgetHandle<W> {
+    /// The widget.
+    pub fn widget(&self) -> &W {
+        &self.widget
+    }
+
+    /// Tell the editor to read the position of the widget again.
+    ///
+    /// Call this after the position returned by
+    /// [`OverlayWidget::position`] changed.
+    pub fn layout(&self) {
+        self.js_editor.layout_overlay_widget(&self.js_widget);
+    }
+}
+impl<W> Drop for OverlayWidgetHandle<W> {
+    fn drop(&mut self) {
+        self.js_editor.remove_overlay_widget(&self.js_widget);
+    }
+}
+impl<W> AsRef<IOverlayWidget> for OverlayWidgetHandle<W> {
+    fn as_ref(&self) -> &IOverlayWidget {
+        &self.js_widget
+    }
+}
+impl<W: fmt::Debug> fmt::Debug for OverlayWidgetHandle<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OverlayWidgetHandle")
+            .field("widget", &self.widget)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,329 @@
+//! Monaco editor as a [Dioxus](https://dioxuslabs.com) component.
+//! Requires the "dioxus" feature and only works with the web renderer.
+use crate::{
+    api::{CodeEditor as CodeEditorModel, TextModel},
+    sys::{
+        editor::{
+            IContentSizeChangedEvent,
+            ICursorPositionChangedEvent,
+            ICursorSelectionChangedEvent,
+            IEditorMouseEvent,
+            IPasteEvent,
+            IStandaloneEditorConstructionOptions,
+        },
+        IKeyboardEvent,
+        IScrollEvent,
+    },
+};
+use dioxus::prelude::*;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+
+type ModelCell = Rc<RefCell<Option<CodeEditorModel>>>;
+
+/// Handle to the editor of a [`CodeEditor`].
+///
+/// Pass it to the `handle` prop to get access to the editor from outside of
+/// the component.
+#[derive(Clone, Debug, Default)]
+pub struct CodeEditorHandle(ModelCell);
+impl CodeEditorHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get access to the underlying [`CodeEditor`](CodeEditorModel).
+    /// The return value is `None` if the editor hasn't been created yet or
+    /// the component was unmounted.
+    pub fn with_editor<T>(&self, f: impl FnOnce(&CodeEditorModel) -> T) -> Option<T> {
+        self.0.borrow().as_ref().map(f)
+    }
+}
+impl PartialEq for CodeEditorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The current event handler props, shared with the editor's listeners so
+/// they always call the latest handlers.
+#[derive(Default)]
+struct Handlers {
+    on_change: Option<EventHandler<String>>,
+    on_did_change_cursor_position: Option<EventHandler<ICursorPositionChangedEvent>>,
+    on_did_change_cursor_selection: Option<EventHandler<ICursorSelectionChangedEvent>>,
+    on_did_content_size_change: Option<EventHandler<IContentSizeChangedEvent>>,
+    on_did_focus_editor_text: Option<EventHandler<()>>,
+    on_did_blur_editor_text: Option<EventHandler<()>>,
+    on_did_paste: Option<EventHandler<IPasteEvent>>,
+    on_did_scroll_change: Option<EventHandler<IScrollEvent>>,
+    on_key_down: Option<EventHandler<IKeyboardEvent>>,
+    on_key_up: Option<EventHandler<IKeyboardEvent>>,
+    on_mouse_down: Option<EventHandler<IEditorMouseEvent>>,
+    on_mouse_up: Option<EventHandler<IEditorMouseEvent>>,
+    on_context_menu: Option<EventHandler<IEditorMouseEvent>>,
+}
+
+/// State of a [`CodeEditor`] that lives as long as the component.
+#[derive(Default)]
+struct EditorState {
+    handle: CodeEditorHandle,
+    handlers: Rc<RefCell<Handlers>>,
+    listeners: RefCell<Vec<Box<dyn Any>>>,
+    /// Set while the `value` prop is applied so the change isn't reported
+    /// back.
+    applying_value: Rc<Cell<bool>>,
+    /// The last value passed to `on_change`. A `value` prop equal to it
+    /// echoes our own change, which may be older than the model.
+    last_emitted: Rc<RefCell<Option<String>>>,
+    /// The `value` prop of the latest render, applied to new models.
+    value: RefCell<Option<String>>,
+}
+impl EditorState {
+    /// Edit the model of the editor to match `value`.
+    fn apply_value(&self, value: &str) {
+        let model = self
+            .handle
+            .with_editor(|editor| editor.get_model())
+            .flatten();
+        if let Some(model) = model {
+            if self.last_emitted.borrow().as_deref() == Some(value) {
+                return;
+            }
+            self.last_emitted.replace(None);
+            self.applying_value.set(true);
+            model.apply_value(value);
+            self.applying_value.set(false);
+        }
+    }
+
+    fn bind_listeners(&self, editor: &CodeEditorModel) {
+        macro_rules! bind {
+            ($($event:ident),* $(,)?) => {{
+                let mut listeners: Vec<Box<dyn Any>> = Vec::new();
+                $(
+                    let handlers = Rc::clone(&self.handlers);
+                    listeners.push(Box::new(editor.$event(move |event| {
+                        // copy the handler out so it can re-render without the borrow
+                        let handler = handlers.borrow().$event;
+                        if let Some(handler) = handler {
+                            handler.call(event);
+                        }
+                    })));
+                )*
+                listeners
+            }};
+        }
+
+        let mut listeners = bind!(
+            on_did_change_cursor_position,
+            on_did_change_cursor_selection,
+            on_did_content_size_change,
+            on_did_paste,
+            on_did_scroll_change,
+            on_key_down,
+            on_key_up,
+            on_mouse_down,
+            on_mouse_up,
+            on_context_menu,
+        );
+
+        let handlers = Rc::clone(&self.handlers);
+        listeners.push(Box::new(editor.on_did_focus_editor_text(move || {
+            let handler = handlers.borrow().on_did_focus_editor_text;
+            if let Some(handler) = handler {
+                handler.call(());
+            }
+        })));
+        let handlers = Rc::clone(&self.handlers);
+        listeners.push(Box::new(editor.on_did_blur_editor_text(move || {
+            let handler = handlers.borrow().on_did_blur_editor_text;
+            if let Some(handler) = handler {
+                handler.call(());
+            }
+        })));
+
+        let handlers = Rc::clone(&self.handlers);
+        let editor_cell = Rc::clone(&self.handle.0);
+        let applying_value = Rc::clone(&self.applying_value);
+        let last_emitted = Rc::clone(&self.last_emitted);
+        listeners.push(Box::new(editor.on_did_change_model_content(move |_| {
+            if applying_value.get() {
+                return;
+            }
+            let handler = handlers.borrow().on_change;
+            let value = editor_cell
+                .borrow()
+                .as_ref()
+                .and_then(|editor| editor.get_model())
+                .map(|model| model.get_value());
+            if let (Some(handler), Some(value)) = (handler, value) {
+                last_emitted.replace(Some(value.clone()));
+                handler.call(value);
+            }
+        })));
+
+        self.listeners.replace(listeners);
+    }
+
+    fn dispose(&self) {
+        // drop the listeners before the editor
+        self.listeners.borrow_mut().clear();
+        self.handle.0.replace(None);
+    }
+}
+
+/// CodeEditor component.
+///
+/// The editor is created once the component is mounted and disposed when it's
+/// unmounted. Changes of `options`, `model` and `value` are applied to the
+/// existing editor.
+#[component]
+pub fn CodeEditor(
+    /// Options used to create the editor.
+    ///
+    /// Later changes are applied with
+    /// [`update_options`](CodeEditorModel::update_options), which ignores the
+    /// options that only apply when the editor is created.
+    options: Option<IStandaloneEditorConstructionOptions>,
+    /// The model shown in the editor.
+    model: Option<TextModel>,
+    /// The text of the editor.
+    ///
+    /// Whenever it changes, the model is edited to match it, keeping the
+    /// cursor and undo history intact.
+    value: Option<String>,
+    /// Called with the text of the editor whenever the user changes it.
+    on_change: Option<EventHandler<String>>,
+    /// Connected to the editor once it has been created.
+    ///
+    /// Only the handle given on the first render is used, changing it
+    /// afterwards has no effect.
+    handle: Option<CodeEditorHandle>,
+    /// Called once the editor has been created.
+    on_editor_created: Option<EventHandler<CodeEditorHandle>>,
+    /// Called when the cursor position has changed.
+    on_did_change_cursor_position: Option<EventHandler<ICursorPositionChangedEvent>>,
+    /// Called when the cursor selection has changed.
+    on_did_change_cursor_selection: Option<EventHandler<ICursorSelectionChangedEvent>>,
+    /// Called when the content width or content height in the editor has
+    /// changed.
+    on_did_content_size_change: Option<EventHandler<IContentSizeChangedEvent>>,
+    /// Called when the text inside this editor gained focus.
+    on_did_focus_editor_text: Option<EventHandler<()>>,
+    /// Called when the text inside this editor lost focus.
+    on_did_blur_editor_text: Option<EventHandler<()>>,
+    /// Called when users paste text in the editor.
+    on_did_paste: Option<EventHandler<IPasteEvent>>,
+    /// Called when the scroll in the editor has changed.
+    on_did_scroll_change: Option<EventHandler<IScrollEvent>>,
+    /// Called on a "keydown".
+    on_key_down: Option<EventHandler<IKeyboardEvent>>,
+    /// Called on a "keyup".
+    on_key_up: Option<EventHandler<IKeyboardEvent>>,
+    /// Called on a "mousedown".
+    on_mouse_down: Option<EventHandler<IEditorMouseEvent>>,
+    /// Called on a "mouseup".
+    on_mouse_up: Option<EventHandler<IEditorMouseEvent>>,
+    /// Called on a "contextmenu".
+    on_context_menu: Option<EventHandler<IEditorMouseEvent>>,
+    class: Option<String>,
+) -> Element {
+    let state = use_hook(|| {
+        Rc::new(EditorState {
+            handle: handle.unwrap_or_default(),
+            ..EditorState::default()
+        })
+    });
+    state.value.replace(value.clone());
+    state.handlers.replace(Handlers {
+        on_change,
+        on_did_change_cursor_position,
+        on_did_change_cursor_selection,
+        on_did_content_size_change,
+        on_did_focus_editor_text,
+        on_did_blur_editor_text,
+        on_did_paste,
+        on_did_scroll_change,
+        on_key_down,
+        on_key_up,
+        on_mouse_down,
+        on_mouse_up,
+        on_context_menu,
+    });
+
+    {
+        let state = Rc::clone(&state);
+        use_drop(move || state.dispose());
+    }
+
+    {
+        let state = Rc::clone(&state);
+        use_effect(use_reactive!(|options| {
+            if let Some(options) = &options {
+                state
+                    .handle
+                    .with_editor(|editor| editor.update_options(options.unchecked_ref()));
+            }
+        }));
+    }
+    {
+        let state = Rc::clone(&state);
+        use_effect(use_reactive!(|model| {
+            if let Some(model) = &model {
+                // the last emitted value belongs to the old model
+                state.last_emitted.replace(None);
+                state.handle.with_editor(|editor| editor.set_model(model));
+                let value = state.value.borrow().clone();
+                if let Some(value) = &value {
+                    state.apply_value(value);
+                }
+            }
+        }));
+    }
+    {
+        let state = Rc::clone(&state);
+        use_effect(use_reactive!(|value| {
+            if let Some(value) = &value {
+                state.apply_value(value);
+            }
+        }));
+    }
+
+    let onmounted = {
+        let state = Rc::clone(&state);
+        move |event: MountedEvent| {
+            if state.handle.with_editor(|_| ()).is_some() {
+                return;
+            }
+            // the web renderer mounts `web_sys::Element`s
+            let data = event.data();
+            let Some(element) = data.downcast::<web_sys::Element>() else {
+                return;
+            };
+            let element: &HtmlElement = element.unchecked_ref();
+
+            let editor = CodeEditorModel::create(element, options.clone());
+            if let Some(model) = &model {
+                editor.set_model(model);
+            }
+            state.bind_listeners(&editor);
+            state.handle.0.replace(Some(editor));
+            if let Some(value) = &value {
+                state.apply_value(value);
+            }
+            if let Some(on_editor_created) = on_editor_created {
+                on_editor_created.call(state.handle.clone());
+            }
+        }
+    };
+
+    rsx! {
+        div { class, onmounted }
+    }
+}
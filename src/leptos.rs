@@ -0,0 +1,345 @@
+//! Monaco editor as a [Leptos](https://leptos.dev) component.
+//! Requires the "leptos" feature.
+use crate::{
+    api::{CodeEditor as CodeEditorModel, DisposableClosure, TextModel, ThemeRegistry},
+    sys::{
+        editor::{
+            IContentSizeChangedEvent,
+            ICursorPositionChangedEvent,
+            ICursorSelectionChangedEvent,
+            IEditorMouseEvent,
+            IPasteEvent,
+            IStandaloneEditorConstructionOptions,
+        },
+        IKeyboardEvent,
+        IScrollEvent,
+    },
+};
+use leptos::{html::Div, prelude::*};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+
+type ModelCell = Rc<RefCell<Option<CodeEditorModel>>>;
+
+/// Handle to the editor of a [`CodeEditor`].
+///
+/// Pass it to the `handle` prop to get access to the editor from outside of
+/// the component.
+#[derive(Clone, Debug, Default)]
+pub struct CodeEditorHandle(ModelCell);
+impl CodeEditorHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get access to the underlying [`CodeEditor`](CodeEditorModel).
+    /// The return value is `None` if the editor hasn't been created yet or
+    /// the component was unmounted.
+    pub fn with_editor<T>(&self, f: impl FnOnce(&CodeEditorModel) -> T) -> Option<T> {
+        self.0.borrow().as_ref().map(f)
+    }
+}
+impl PartialEq for CodeEditorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// State of a mounted [`CodeEditor`].
+/// The editor is disposed when this is dropped.
+struct Mounted {
+    editor: ModelCell,
+    listeners: Vec<Box<dyn Any>>,
+}
+impl Drop for Mounted {
+    fn drop(&mut self) {
+        // drop the listeners before the editor
+        self.listeners.clear();
+        self.editor.replace(None);
+    }
+}
+
+fn bind_event<T: 'static>(
+    listeners: &mut Vec<Box<dyn Any>>,
+    callback: Option<Callback<T>>,
+    bind: impl FnOnce(Box<dyn FnMut(T)>) -> DisposableClosure<dyn FnMut(T)>,
+) {
+    if let Some(callback) = callback {
+        listeners.push(Box::new(bind(Box::new(move |event| callback.run(event)))));
+    }
+}
+
+fn bind_unit_event(
+    listeners: &mut Vec<Box<dyn Any>>,
+    callback: Option<Callback<()>>,
+    bind: impl FnOnce(Box<dyn FnMut()>) -> DisposableClosure<dyn FnMut()>,
+) {
+    if let Some(callback) = callback {
+        listeners.push(Box::new(bind(Box::new(move || callback.run(())))));
+    }
+}
+
+/// CodeEditor component.
+///
+/// All props except for the callbacks and `handle` are reactive and applied
+/// to the existing editor.
+#[component]
+pub fn CodeEditor(
+    /// Options used to create the editor.
+    ///
+    /// Later changes are applied with
+    /// [`update_options`](CodeEditorModel::update_options), which ignores the
+    /// options that only apply when the editor is created.
+    /// Pass a local signal or a fixed value using `MaybeProp::from_local`.
+    #[prop(optional, into)]
+    options: MaybeProp<IStandaloneEditorConstructionOptions, LocalStorage>,
+    /// The model shown in the editor.
+    ///
+    /// Pass a local signal or a fixed model using `MaybeProp::from_local`.
+    #[prop(optional, into)]
+    model: MaybeProp<TextModel, LocalStorage>,
+    /// The text of the editor.
+    ///
+    /// Whenever it changes, the model is edited to match it, keeping the
+    /// cursor and undo history intact.
+    #[prop(optional, into)]
+    value: MaybeProp<String>,
+    /// The language of the model shown in the editor.
+    #[prop(optional, into)]
+    language: MaybeProp<String>,
+    /// The theme used by the editor.
+    ///
+    /// Monaco only supports a single theme at a time, so this changes the
+    /// theme of all editors. The theme is applied through
+    /// [`ThemeRegistry::global`], which stops it from following the color
+    /// scheme.
+    #[prop(optional, into)]
+    theme: MaybeProp<String>,
+    /// Called with the text of the editor whenever the user changes it.
+    #[prop(optional, into)]
+    on_change: Option<Callback<String>>,
+    /// Connected to the editor once it has been created.
+    #[prop(optional)]
+    handle: Option<CodeEditorHandle>,
+    /// Called once the editor has been created.
+    #[prop(optional, into)]
+    on_editor_created: Option<Callback<CodeEditorHandle>>,
+    /// Called when the cursor position has changed.
+    #[prop(optional, into)]
+    on_did_change_cursor_position: Option<Callback<ICursorPositionChangedEvent>>,
+    /// Called when the cursor selection has changed.
+    #[prop(optional, into)]
+    on_did_change_cursor_selection: Option<Callback<ICursorSelectionChangedEvent>>,
+    /// Called when the content width or content height in the editor has
+    /// changed.
+    #[prop(optional, into)]
+    on_did_content_size_change: Option<Callback<IContentSizeChangedEvent>>,
+    /// Called when the text inside this editor gained focus.
+    #[prop(optional, into)]
+    on_did_focus_editor_text: Option<Callback<()>>,
+    /// Called when the text inside this editor lost focus.
+    #[prop(optional, into)]
+    on_did_blur_editor_text: Option<Callback<()>>,
+    /// Called when users paste text in the editor.
+    #[prop(optional, into)]
+    on_did_paste: Option<Callback<IPasteEvent>>,
+    /// Called when the scroll in the editor has changed.
+    #[prop(optional, into)]
+    on_did_scroll_change: Option<Callback<IScrollEvent>>,
+    /// Called on a "keydown".
+    #[prop(optional, into)]
+    on_key_down: Option<Callback<IKeyboardEvent>>,
+    /// Called on a "keyup".
+    #[prop(optional, into)]
+    on_key_up: Option<Callback<IKeyboardEvent>>,
+    /// Called on a "mousedown".
+    #[prop(optional, into)]
+    on_mouse_down: Option<Callback<IEditorMouseEvent>>,
+    /// Called on a "mouseup".
+    #[prop(optional, into)]
+    on_mouse_up: Option<Callback<IEditorMouseEvent>>,
+    /// Called on a "contextmenu".
+    #[prop(optional, into)]
+    on_context_menu: Option<Callback<IEditorMouseEvent>>,
+    #[prop(optional, into)] class: Option<String>,
+) -> impl IntoView {
+    let node_ref = NodeRef::<Div>::new();
+    let handle = handle.unwrap_or_default();
+    let mounted = StoredValue::new_local(None::<Mounted>);
+    let is_mounted = RwSignal::new(false);
+    // set while the `value` prop is applied so the change isn't reported back
+    let applying_value = Rc::new(Cell::new(false));
+    // the last value passed to `on_change`, a `value` equal to it echoes our own
+    // change and may be older than the model
+    let last_emitted = Rc::new(RefCell::new(None::<String>));
+
+    // edit the model shown in the editor to match `value`
+    let apply_value = {
+        let handle = handle.clone();
+        let applying_value = Rc::clone(&applying_value);
+        let last_emitted = Rc::clone(&last_emitted);
+        move |value: &String| {
+            let model = handle.with_editor(|editor| editor.get_model()).flatten();
+            if let Some(model) = model {
+                if last_emitted.borrow().as_ref() == Some(value) {
+                    return;
+                }
+                last_emitted.replace(None);
+                applying_value.set(true);
+                model.apply_value(value);
+                applying_value.set(false);
+            }
+        }
+    };
+
+    let mut constructed = false;
+    Effect::new({
+        let handle = handle.clone();
+        let applying_value = Rc::clone(&applying_value);
+        let last_emitted = Rc::clone(&last_emitted);
+        move |_| {
+            let Some(element) = node_ref.get() else {
+                return;
+            };
+            if constructed {
+                return;
+            }
+            constructed = true;
+
+            let element: &HtmlElement = &element;
+            let editor = CodeEditorModel::create(element, options.get_untracked());
+            if let Some(model) = &model.get_untracked() {
+                editor.set_model(model);
+            }
+
+            let mut listeners: Vec<Box<dyn Any>> = Vec::new();
+            if let Some(on_change) = on_change {
+                let editor_cell = Rc::clone(&handle.0);
+                let applying_value = Rc::clone(&applying_value);
+                let last_emitted = Rc::clone(&last_emitted);
+                listeners.push(Box::new(editor.on_did_change_model_content(move |_| {
+                    if applying_value.get() {
+                        return;
+                    }
+                    let value = editor_cell
+                        .borrow()
+                        .as_ref()
+                        .and_then(|editor| editor.get_model())
+                        .map(|model| model.get_value());
+                    if let Some(value) = value {
+                        last_emitted.replace(Some(value.clone()));
+                        on_change.run(value);
+                    }
+                })));
+            }
+            bind_event(&mut listeners, on_did_change_cursor_position, |f| {
+                editor.on_did_change_cursor_position(f)
+            });
+            bind_event(&mut listeners, on_did_change_cursor_selection, |f| {
+                editor.on_did_change_cursor_selection(f)
+            });
+            bind_event(&mut listeners, on_did_content_size_change, |f| {
+                editor.on_did_content_size_change(f)
+            });
+            bind_unit_event(&mut listeners, on_did_focus_editor_text, |f| {
+                editor.on_did_focus_editor_text(f)
+            });
+            bind_unit_event(&mut listeners, on_did_blur_editor_text, |f| {
+                editor.on_did_blur_editor_text(f)
+            });
+            bind_event(&mut listeners, on_did_paste, |f| editor.on_did_paste(f));
+            bind_event(&mut listeners, on_did_scroll_change, |f| {
+                editor.on_did_scroll_change(f)
+            });
+            bind_event(&mut listeners, on_key_down, |f| editor.on_key_down(f));
+            bind_event(&mut listeners, on_key_up, |f| editor.on_key_up(f));
+            bind_event(&mut listeners, on_mouse_down, |f| editor.on_mouse_down(f));
+            bind_event(&mut listeners, on_mouse_up, |f| editor.on_mouse_up(f));
+            bind_event(&mut listeners, on_context_menu, |f| {
+                editor.on_context_menu(f)
+            });
+
+            handle.0.replace(Some(editor));
+            mounted.set_value(Some(Mounted {
+                editor: Rc::clone(&handle.0),
+                listeners,
+            }));
+            is_mounted.set(true);
+            if let Some(on_editor_created) = on_editor_created {
+                on_editor_created.run(handle.clone());
+            }
+        }
+    });
+
+    Effect::new({
+        let handle = handle.clone();
+        move |_| {
+            let options = options.get();
+            if !is_mounted.get() {
+                return;
+            }
+            if let Some(options) = options {
+                handle.with_editor(|editor| editor.update_options(options.unchecked_ref()));
+            }
+        }
+    });
+
+    Effect::new({
+        let handle = handle.clone();
+        let apply_value = apply_value.clone();
+        move |_| {
+            let model = model.get();
+            if !is_mounted.get() {
+                return;
+            }
+            if let Some(model) = &model {
+                // the last emitted value belongs to the old model
+                last_emitted.replace(None);
+                handle.with_editor(|editor| editor.set_model(model));
+                if let Some(language) = language.get_untracked() {
+                    model.set_language(&language);
+                }
+                if let Some(value) = value.get_untracked() {
+                    apply_value(&value);
+                }
+            }
+        }
+    });
+
+    Effect::new(move |_| {
+        let value = value.get();
+        if !is_mounted.get() {
+            return;
+        }
+        if let Some(value) = &value {
+            apply_value(value);
+        }
+    });
+
+    Effect::new({
+        let handle = handle.clone();
+        move |_| {
+            let language = language.get();
+            if !is_mounted.get() {
+                return;
+            }
+            let model = handle.with_editor(|editor| editor.get_model()).flatten();
+            if let (Some(language), Some(model)) = (language, model) {
+                model.set_language(&language);
+            }
+        }
+    });
+
+    Effect::new(move |_| {
+        if let Some(theme) = theme.get() {
+            ThemeRegistry::global().set_theme(&theme);
+        }
+    });
+
+    view! { <div node_ref=node_ref class=class></div> }
+}
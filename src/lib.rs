@@ -3,6 +3,12 @@
 mod macros;
 #[cfg(feature = "api")]
 pub mod api;
+#[cfg(feature = "dioxus")]
+pub mod dioxus;
+#[cfg(feature = "leptos")]
+pub mod leptos;
+#[cfg(feature = "sycamore")]
+pub mod sycamore;
 pub mod sys;
 #[cfg(feature = "workers")]
 pub mod workers;
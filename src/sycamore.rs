@@ -0,0 +1,252 @@
+//! Monaco editor as a [Sycamore](https://sycamore.dev) component.
+//! Requires the "sycamore" feature.
+use crate::{
+    api::{CodeEditor as CodeEditorModel, TextModel, ThemeRegistry},
+    sys::editor::IStandaloneEditorConstructionOptions,
+};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+use sycamore::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+
+type ModelCell = Rc<RefCell<Option<CodeEditorModel>>>;
+
+/// Handle to the editor of a [`CodeEditor`].
+///
+/// Pass it to the `handle` prop to get access to the editor from outside of
+/// the component.
+#[derive(Clone, Debug, Default)]
+pub struct CodeEditorHandle(ModelCell);
+impl CodeEditorHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get access to the underlying [`CodeEditor`](CodeEditorModel).
+    /// The return value is `None` if the editor hasn't been created yet or
+    /// the component was unmounted.
+    pub fn with_editor<T>(&self, f: impl FnOnce(&CodeEditorModel) -> T) -> Option<T> {
+        self.0.borrow().as_ref().map(f)
+    }
+}
+impl PartialEq for CodeEditorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// State of a mounted [`CodeEditor`].
+/// The editor is disposed when this is dropped.
+struct Mounted {
+    editor: ModelCell,
+    listeners: Vec<Box<dyn Any>>,
+}
+impl Drop for Mounted {
+    fn drop(&mut self) {
+        // drop the listeners before the editor
+        self.listeners.clear();
+        self.editor.replace(None);
+    }
+}
+
+#[derive(Props)]
+pub struct CodeEditorProps {
+    /// Options used to create the editor.
+    ///
+    /// Later changes are applied with
+    /// [`update_options`](CodeEditorModel::update_options), which ignores the
+    /// options that only apply when the editor is created.
+    #[prop(default = None.into(), setter(into))]
+    pub options: MaybeDyn<Option<IStandaloneEditorConstructionOptions>>,
+    /// The model shown in the editor.
+    #[prop(default = None.into(), setter(into))]
+    pub model: MaybeDyn<Option<TextModel>>,
+    /// The text of the editor.
+    ///
+    /// The binding goes both ways: whenever the signal changes, the model is
+    /// edited to match it, keeping the cursor and undo history intact, and
+    /// whenever the user changes the text, the signal is set to it.
+    #[prop(default, setter(strip_option))]
+    pub value: Option<Signal<String>>,
+    /// The language of the model shown in the editor.
+    #[prop(default = None.into(), setter(into))]
+    pub language: MaybeDyn<Option<String>>,
+    /// The theme used by the editor.
+    ///
+    /// Monaco only supports a single theme at a time, so this changes the
+    /// theme of all editors. The theme is applied through
+    /// [`ThemeRegistry::global`], which stops it from following the color
+    /// scheme.
+    #[prop(default = None.into(), setter(into))]
+    pub theme: MaybeDyn<Option<String>>,
+    /// Connected to the editor once it has been created.
+    #[prop(default, setter(strip_option))]
+    pub handle: Option<CodeEditorHandle>,
+    /// Called once the editor has been created.
+    #[prop(default, setter(strip_option))]
+    pub on_editor_created: Option<Box<dyn FnOnce(CodeEditorHandle)>>,
+    #[prop(default, setter(into))]
+    pub class: String,
+}
+
+/// CodeEditor component.
+///
+/// All props except for `handle` and `on_editor_created` are reactive and
+/// applied to the existing editor. The editor is disposed when the component
+/// is unmounted.
+#[component]
+pub fn CodeEditor(props: CodeEditorProps) -> View {
+    let CodeEditorProps {
+        options,
+        model,
+        value,
+        language,
+        theme,
+        handle,
+        on_editor_created,
+        class,
+    } = props;
+
+    let node_ref = create_node_ref();
+    let handle = handle.unwrap_or_default();
+    let mounted = Rc::new(RefCell::new(None::<Mounted>));
+    let is_mounted = create_signal(false);
+    // set while `value` is applied so the change isn't written back
+    let applying_value = Rc::new(Cell::new(false));
+
+    // edit the model shown in the editor to match `value`
+    let apply_value = {
+        let handle = handle.clone();
+        let applying_value = Rc::clone(&applying_value);
+        move |value: &str| {
+            let model = handle.with_editor(|editor| editor.get_model()).flatten();
+            if let Some(model) = model {
+                applying_value.set(true);
+                model.apply_value(value);
+                applying_value.set(false);
+            }
+        }
+    };
+
+    on_mount({
+        let handle = handle.clone();
+        let options = options.clone();
+        let model = model.clone();
+        let mounted = Rc::clone(&mounted);
+        let apply_value = apply_value.clone();
+        move || {
+            let Some(element) = node_ref.try_get() else {
+                return;
+            };
+            let element: &HtmlElement = element.unchecked_ref();
+            let (options, model) = untrack(|| (options.get_clone(), model.get_clone()));
+            let editor = CodeEditorModel::create(element, options);
+            if let Some(model) = &model {
+                editor.set_model(model);
+            }
+
+            let mut listeners: Vec<Box<dyn Any>> = Vec::new();
+            if let Some(value) = value {
+                let editor_cell = Rc::clone(&handle.0);
+                listeners.push(Box::new(editor.on_did_change_model_content(move |_| {
+                    if applying_value.get() {
+                        return;
+                    }
+                    let text = editor_cell
+                        .borrow()
+                        .as_ref()
+                        .and_then(|editor| editor.get_model())
+                        .map(|model| model.get_value());
+                    if let Some(text) = text {
+                        value.set(text);
+                    }
+                })));
+            }
+
+            handle.0.replace(Some(editor));
+            mounted.replace(Some(Mounted {
+                editor: Rc::clone(&handle.0),
+                listeners,
+            }));
+            if let Some(value) = value {
+                value.with_untracked(|value| apply_value(value));
+            }
+            is_mounted.set(true);
+            if let Some(on_editor_created) = on_editor_created {
+                on_editor_created(handle.clone());
+            }
+        }
+    });
+    on_cleanup(move || drop(mounted.take()));
+
+    create_effect({
+        let handle = handle.clone();
+        move || {
+            let options = options.get_clone();
+            if !is_mounted.get() {
+                return;
+            }
+            if let Some(options) = options {
+                handle.with_editor(|editor| editor.update_options(options.unchecked_ref()));
+            }
+        }
+    });
+
+    create_effect({
+        let handle = handle.clone();
+        let language = language.clone();
+        let apply_value = apply_value.clone();
+        move || {
+            let model = model.get_clone();
+            if !is_mounted.get() {
+                return;
+            }
+            if let Some(model) = &model {
+                handle.with_editor(|editor| editor.set_model(model));
+                if let Some(language) = untrack(|| language.get_clone()) {
+                    model.set_language(&language);
+                }
+                if let Some(value) = value {
+                    value.with_untracked(|value| apply_value(value));
+                }
+            }
+        }
+    });
+
+    create_effect(move || {
+        let Some(value) = value else {
+            return;
+        };
+        value.track();
+        if !is_mounted.get() {
+            return;
+        }
+        value.with_untracked(|value| apply_value(value));
+    });
+
+    create_effect({
+        let handle = handle.clone();
+        move || {
+            let language = language.get_clone();
+            if !is_mounted.get() {
+                return;
+            }
+            let model = handle.with_editor(|editor| editor.get_model()).flatten();
+            if let (Some(language), Some(model)) = (language, model) {
+                model.set_language(&language);
+            }
+        }
+    });
+
+    create_effect(move || {
+        if let Some(theme) = theme.get_clone() {
+            ThemeRegistry::global().set_theme(&theme);
+        }
+    });
+
+    view! { div(r#ref=node_ref, class=class) }
+}
@@ -1355,7 +1355,7 @@ extern "C" {
 
 #[wasm_bindgen]
 extern "C" {
-    #[derive(Debug)]
+    #[derive(Clone, Debug, Eq, PartialEq)]
     #[wasm_bindgen(extends = Object)]
     pub type InlineCompletions;
     /// Type: `readonly TItem[]`
@@ -2479,7 +2479,7 @@ extern "C" {
 
 #[wasm_bindgen]
 extern "C" {
-    #[derive(Debug)]
+    #[derive(Clone, Debug, Eq, PartialEq)]
     #[wasm_bindgen(extends = Object)]
     pub type Command;
     #[wasm_bindgen(method, js_class = "Command", js_name = "id", js_namespace = languages, getter = id)]
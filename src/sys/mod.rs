@@ -482,7 +482,7 @@ extern "C" {
 
 #[wasm_bindgen]
 extern "C" {
-    #[derive(Debug)]
+    #[derive(Clone, Debug)]
     #[wasm_bindgen(extends = Object)]
     pub type CancellationToken;
     #[wasm_bindgen(method, js_class = "CancellationToken", js_name = "isCancellationRequested", getter = isCancellationRequested)]
@@ -657,7 +657,7 @@ extern "C" {
 #[wasm_bindgen]
 extern "C" {
     /// A range in the editor. This interface is suitable for serialization.
-    #[derive(Debug)]
+    #[derive(Clone, Debug, Eq, PartialEq)]
     #[wasm_bindgen(extends = Object)]
     pub type IRange;
     /// Line number on which the range starts (starts at 1).
@@ -0,0 +1,197 @@
+use crate::api::{
+    DiffEditor as DiffEditorModel,
+    DiffEditorOptions,
+    DisposableClosure,
+    LineChange,
+    TextModel,
+};
+use std::{cell::RefCell, rc::Rc};
+use web_sys::HtmlElement;
+use yew::{html, Callback, Classes, Component, Context, Html, NodeRef, Properties};
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct DiffEditorProps {
+    #[prop_or_default]
+    pub link: Option<DiffEditorLink>,
+    /// Changing the options will cause the editor to be re-created.
+    #[prop_or_default]
+    pub options: Option<DiffEditorOptions>,
+    /// The model shown on the original side.
+    /// The editor only shows a diff if both `original` and `modified` are
+    /// given.
+    #[prop_or_default]
+    pub original: Option<TextModel>,
+    /// The model shown on the modified side.
+    #[prop_or_default]
+    pub modified: Option<TextModel>,
+    /// This could be called multiple times if the `options` field changes.
+    /// You can use this to initialise the editor
+    #[prop_or_default]
+    pub on_editor_created: Callback<DiffEditorLink>,
+    /// Called with the line changes whenever the diff has been computed.
+    #[prop_or_default]
+    pub on_diff_updated: Callback<Vec<LineChange>>,
+    #[prop_or_default]
+    pub classes: Classes,
+}
+
+type ModelCell = Rc<RefCell<Option<DiffEditorModel>>>;
+
+/// DiffEditor component.
+#[derive(Debug)]
+pub struct DiffEditor {
+    node_ref: NodeRef,
+    editor: ModelCell,
+    /// The current `on_diff_updated` prop, shared with the event listener.
+    on_diff_updated: Rc<RefCell<Callback<Vec<LineChange>>>>,
+    diff_listener: Option<DisposableClosure<dyn FnMut()>>,
+}
+impl DiffEditor {
+    fn emit_editor_created(&self, ctx: &Context<Self>) {
+        let DiffEditorProps {
+            link,
+            on_editor_created,
+            ..
+        } = &ctx.props();
+        // reuse the link we were given or create a new one
+        let link = link
+            .clone()
+            .unwrap_or_else(|| DiffEditorLink::new_connected(self.editor.clone()));
+        on_editor_created.emit(link);
+    }
+}
+impl Component for DiffEditor {
+    type Message = ();
+    type Properties = DiffEditorProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let editor = ModelCell::default();
+        if let Some(editor_link) = &ctx.props().link {
+            editor_link.connect(editor.clone());
+        }
+        Self {
+            node_ref: NodeRef::default(),
+            editor,
+            on_diff_updated: Rc::new(RefCell::new(ctx.props().on_diff_updated.clone())),
+            diff_listener: None,
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        // these are the new values
+        let DiffEditorProps {
+            link,
+            options,
+            original,
+            modified,
+            on_diff_updated,
+            ..
+        } = &ctx.props();
+
+        let mut should_render = false;
+        if link != &old_props.link {
+            // make sure to connect the new link to this component
+            if let Some(link) = &link {
+                link.connect(self.editor.clone());
+            }
+        }
+        if on_diff_updated != &old_props.on_diff_updated {
+            self.on_diff_updated.replace(on_diff_updated.clone());
+        }
+        // changing options requires re-create
+        if options != &old_props.options {
+            should_render = true;
+        }
+        // swap the models without re-creating the editor
+        if original != &old_props.original || modified != &old_props.modified {
+            if let Some(editor) = &*self.editor.borrow() {
+                match (original, modified) {
+                    (Some(original), Some(modified)) => editor.set_model(original, modified),
+                    _ => {
+                        editor.detach_model();
+                    }
+                }
+            }
+        }
+
+        if should_render {
+            // if we're gonna re-create the editor we need to clean up the old one first so
+            // as not to cause issues
+            self.diff_listener = None;
+            self.editor.replace(None);
+        }
+
+        should_render
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let Self {
+            node_ref, editor, ..
+        } = self;
+        let props = ctx.props();
+
+        debug_assert!(
+            editor.borrow().is_none(),
+            "previous editor must be disposed before re-creating"
+        );
+
+        html! {
+            <div ref={node_ref.clone()} class={props.classes.clone()} />
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let el = self
+            .node_ref
+            .cast::<HtmlElement>()
+            .expect("failed to resolve editor element");
+
+        let props = ctx.props();
+        let editor = DiffEditorModel::create(&el, props.options.clone());
+
+        if let (Some(original), Some(modified)) = (&props.original, &props.modified) {
+            editor.set_model(original, modified);
+        }
+
+        let editor_cell = Rc::clone(&self.editor);
+        let on_diff_updated = Rc::clone(&self.on_diff_updated);
+        self.diff_listener = Some(editor.on_did_update_diff(move || {
+            if let Some(editor) = &*editor_cell.borrow() {
+                let changes = editor.line_changes().unwrap_or_default();
+                on_diff_updated.borrow().emit(changes);
+            }
+        }));
+
+        self.editor.replace(Some(editor));
+        self.emit_editor_created(ctx);
+    }
+}
+
+/// Link to control a [`DiffEditor`].
+#[derive(Clone, Debug, Default)]
+pub struct DiffEditorLink(RefCell<ModelCell>);
+
+impl DiffEditorLink {
+    pub fn new() -> Self {
+        Self(RefCell::default())
+    }
+
+    fn new_connected(model_cell: ModelCell) -> Self {
+        Self(RefCell::new(model_cell))
+    }
+
+    fn connect(&self, model_cell: ModelCell) {
+        self.0.replace(model_cell);
+    }
+
+    /// Get access to the underlying [`DiffEditor`](DiffEditorModel).
+    /// The return value is `None` if the link isn't connected.
+    pub fn with_editor<T>(&self, f: impl FnOnce(&DiffEditorModel) -> T) -> Option<T> {
+        self.0.borrow().borrow().as_ref().map(f)
+    }
+}
+impl PartialEq for DiffEditorLink {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0.borrow(), &other.0.borrow())
+    }
+}
@@ -0,0 +1,119 @@
+//! Binding the event callback props of [`CodeEditor`](super::CodeEditor).
+use super::CodeEditorProps;
+use crate::{
+    api::CodeEditor as CodeEditorModel,
+    sys::{
+        editor::{
+            ConfigurationChangedEvent,
+            EditorLayoutInfo,
+            IContentSizeChangedEvent,
+            ICursorPositionChangedEvent,
+            ICursorSelectionChangedEvent,
+            IEditorMouseEvent,
+            IModelChangedEvent,
+            IModelContentChangedEvent,
+            IModelLanguageChangedEvent,
+            IModelOptionsChangedEvent,
+            IPasteEvent,
+        },
+        IKeyboardEvent,
+        IScrollEvent,
+    },
+};
+use std::{any::Any, cell::RefCell, fmt, rc::Rc};
+use wasm_bindgen::JsValue;
+use yew::Callback;
+
+/// A listener bound to an editor event.
+struct EventListener<T: 'static> {
+    /// The current callback prop, shared with the listener so it can be
+    /// swapped without binding again.
+    callback: Rc<RefCell<Callback<T>>>,
+    _disposable: Box<dyn Any>,
+}
+
+macro_rules! event_listeners {
+    (@ty) => { () };
+    (@ty $arg:ty) => { $arg };
+    (@bind $editor:ident.$event:ident($callback:ident)) => {{
+        let callback = Rc::clone(&$callback);
+        Box::new($editor.$event(move || callback.borrow().emit(()))) as Box<dyn Any>
+    }};
+    (@bind $editor:ident.$event:ident($callback:ident, $arg:ty)) => {{
+        let callback = Rc::clone(&$callback);
+        Box::new($editor.$event(move |event: $arg| callback.borrow().emit(event))) as Box<dyn Any>
+    }};
+    ($($event:ident($($arg:ty)?);)*) => {
+        /// Listeners for the event callback props of
+        /// [`CodeEditor`](super::CodeEditor).
+        #[derive(Default)]
+        pub(super) struct EventListeners {
+            $(
+                $event: Option<EventListener<event_listeners!(@ty $($arg)?)>>,
+            )*
+        }
+        impl EventListeners {
+            /// Bind, update or unbind the listeners to match the props.
+            ///
+            /// Only events that have a callback are listened to.
+            pub(super) fn update(&mut self, editor: &CodeEditorModel, props: &CodeEditorProps) {
+                $(
+                    match (&mut self.$event, &props.$event) {
+                        (Some(listener), Some(callback)) => {
+                            listener.callback.replace(callback.clone());
+                        }
+                        (listener @ None, Some(callback)) => {
+                            let callback = Rc::new(RefCell::new(callback.clone()));
+                            let disposable =
+                                event_listeners!(@bind editor.$event(callback $(, $arg)?));
+                            *listener = Some(EventListener {
+                                callback,
+                                _disposable: disposable,
+                            });
+                        }
+                        (listener, None) => *listener = None,
+                    }
+                )*
+            }
+        }
+        impl fmt::Debug for EventListeners {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut bound = Vec::new();
+                $(
+                    if self.$event.is_some() {
+                        bound.push(stringify!($event));
+                    }
+                )*
+                f.debug_struct("EventListeners").field("bound", &bound).finish()
+            }
+        }
+    };
+}
+
+event_listeners! {
+    on_context_menu(IEditorMouseEvent);
+    on_did_blur_editor_text();
+    on_did_blur_editor_widget();
+    on_did_change_configuration(ConfigurationChangedEvent);
+    on_did_change_cursor_position(ICursorPositionChangedEvent);
+    on_did_change_cursor_selection(ICursorSelectionChangedEvent);
+    on_did_change_model(IModelChangedEvent);
+    on_did_change_model_content(IModelContentChangedEvent);
+    on_did_change_model_decorations(JsValue);
+    on_did_change_model_language(IModelLanguageChangedEvent);
+    on_did_change_model_language_configuration(JsValue);
+    on_did_change_model_options(IModelOptionsChangedEvent);
+    on_did_content_size_change(IContentSizeChangedEvent);
+    on_did_dispose();
+    on_did_focus_editor_text();
+    on_did_focus_editor_widget();
+    on_did_layout_change(EditorLayoutInfo);
+    on_did_paste(IPasteEvent);
+    on_did_scroll_change(IScrollEvent);
+    on_key_down(IKeyboardEvent);
+    on_key_up(IKeyboardEvent);
+    on_mouse_down(IEditorMouseEvent);
+    on_mouse_leave(IEditorMouseEvent);
+    on_mouse_move(IEditorMouseEvent);
+    on_mouse_up(IEditorMouseEvent);
+}
@@ -0,0 +1,158 @@
+//! Hooks for using the editor in function components.
+use super::CodeEditorContext;
+use crate::{
+    api::{CodeEditor as CodeEditorModel, Decoration, DisposableClosure, Marker, TextModel},
+    sys::Uri,
+};
+use std::{cell::RefCell, collections::HashMap};
+use wasm_bindgen::JsValue;
+use yew::{hook, use_context, use_effect_with, use_memo, use_mut_ref, Callback};
+
+thread_local! {
+    /// The models used by [`use_text_model`] by model id, with the number of
+    /// components using them and whether the hook created them.
+    static MODEL_USERS: RefCell<HashMap<String, (usize, bool)>> = RefCell::default();
+}
+
+/// An event method of [`CodeEditor`](CodeEditorModel) that passes an
+/// argument to the listener.
+pub type EditorEvent<T> =
+    fn(&CodeEditorModel, Box<dyn FnMut(T)>) -> DisposableClosure<dyn FnMut(T)>;
+
+/// Keeps a model used by [`use_text_model`] alive.
+struct ModelLease {
+    model: TextModel,
+}
+impl ModelLease {
+    fn acquire(uri: &Uri, initial: &str, language: Option<&str>) -> Result<Self, JsValue> {
+        if let Some(model) = TextModel::get(uri) {
+            // models are counted by id, a model with the same uri that
+            // replaced a disposed one starts over
+            let leased = MODEL_USERS.with(|users| {
+                users
+                    .borrow_mut()
+                    .get_mut(&model.id())
+                    .map(|(count, _)| *count += 1)
+                    .is_some()
+            });
+            if leased {
+                return Ok(Self { model });
+            }
+        }
+
+        // only dispose models we created, others belong to someone else
+        let created = TextModel::get(uri).is_none();
+        let model = TextModel::get_or_create(uri, initial, language)?;
+        MODEL_USERS.with(|users| users.borrow_mut().insert(model.id(), (1, created)));
+        Ok(Self { model })
+    }
+}
+impl Drop for ModelLease {
+    fn drop(&mut self) {
+        let id = self.model.id();
+        let dispose = MODEL_USERS.with(|users| {
+            let mut users = users.borrow_mut();
+            match users.get_mut(&id) {
+                Some((count, _)) if *count > 1 => {
+                    *count -= 1;
+                    false
+                }
+                _ => users.remove(&id).is_some_and(|(_, created)| created),
+            }
+        });
+        if dispose && !self.model.as_ref().is_disposed() {
+            self.model.as_ref().dispose();
+        }
+    }
+}
+
+/// Get the model with `uri`, see [`TextModel::get_or_create`].
+///
+/// Components using the same `uri` share the model, only the first one sets
+/// `initial` and `language`. A model created by this hook is disposed once the
+/// last component using it unmounts, while models that already existed are
+/// kept.
+///
+/// Returns the error of creating the model if it failed.
+#[hook]
+pub fn use_text_model(
+    uri: &Uri,
+    initial: &str,
+    language: Option<&str>,
+) -> Result<TextModel, JsValue> {
+    let lease = use_memo(uri.to_string(false), |_| {
+        ModelLease::acquire(uri, initial, language)
+    });
+    match &*lease {
+        Ok(lease) => Ok(lease.model.clone()),
+        Err(err) => Err(err.clone()),
+    }
+}
+
+/// Call `callback` whenever `event` is emitted by the surrounding
+/// [`CodeEditor`](super::CodeEditor).
+///
+/// `event` is one of the event methods of [`CodeEditor`](CodeEditorModel)
+/// that pass an argument to the listener, for example
+/// `CodeEditor::on_did_change_cursor_position`.
+///
+/// The hook uses the [`CodeEditorContext`], so the component must be one of
+/// the `children` of the editor. The listener is bound once the editor has
+/// been created and again whenever it's re-created.
+#[hook]
+pub fn use_editor_event<T: 'static>(event: EditorEvent<T>, callback: Callback<T>) {
+    let current = use_mut_ref(|| callback.clone());
+    *current.borrow_mut() = callback;
+    let context = use_context::<CodeEditorContext>();
+
+    // the context changes whenever the editor is created
+    use_effect_with(context, move |context| {
+        let listener = context.as_ref().and_then(|context| {
+            context.with_editor(|editor| {
+                event(editor, Box::new(move |arg| current.borrow().emit(arg)))
+            })
+        });
+        move || drop(listener)
+    });
+}
+
+/// Show `markers` on `model` under `owner`.
+///
+/// The markers are removed again when the component unmounts or any of the
+/// arguments change.
+#[hook]
+pub fn use_markers(model: &TextModel, owner: &str, markers: &[Marker]) {
+    use_effect_with(
+        (model.clone(), owner.to_owned(), markers.to_vec()),
+        |(model, owner, markers)| {
+            model.set_markers(owner, markers);
+            let model = model.clone();
+            let owner = owner.clone();
+            move || {
+                if !model.as_ref().is_disposed() {
+                    model.set_markers(&owner, &[]);
+                }
+            }
+        },
+    );
+}
+
+/// Apply `decorations` to `model`.
+///
+/// The decorations are removed again when the component unmounts or any of
+/// the arguments change.
+#[hook]
+pub fn use_decorations(model: &TextModel, decorations: &[Decoration]) {
+    use_effect_with(
+        (model.clone(), decorations.to_vec()),
+        |(model, decorations)| {
+            let ids = model.delta_decorations(&[], decorations);
+            let model = model.clone();
+            move || {
+                if !model.as_ref().is_disposed() {
+                    model.delta_decorations(&ids, &[]);
+                }
+            }
+        },
+    );
+}
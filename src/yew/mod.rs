@@ -1,39 +1,349 @@
 //! Monaco editor as a [Yew](https://yew.rs) component.
 //! Requires the "yew" feature.
 use crate::{
-    api::{CodeEditor as CodeEditorModel, TextModel},
-    sys::editor::IStandaloneEditorConstructionOptions,
+    api::{
+        CodeEditor as CodeEditorModel,
+        DisposableClosure,
+        EditorOptions,
+        TextModel,
+        ThemeRegistry,
+    },
+    sys::{
+        editor::{
+            ConfigurationChangedEvent,
+            EditorLayoutInfo,
+            IContentSizeChangedEvent,
+            ICursorPositionChangedEvent,
+            ICursorSelectionChangedEvent,
+            IEditorMouseEvent,
+            IModelChangedEvent,
+            IModelContentChangedEvent,
+            IModelLanguageChangedEvent,
+            IModelOptionsChangedEvent,
+            IPasteEvent,
+            IStandaloneEditorConstructionOptions,
+        },
+        IKeyboardEvent,
+        IScrollEvent,
+    },
 };
+use events::EventListeners;
 use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use web_sys::HtmlElement;
-use yew::{html, Callback, Classes, Component, Context, Html, NodeRef, Properties};
+use yew::{
+    html,
+    AttrValue,
+    Callback,
+    Children,
+    Classes,
+    Component,
+    Context,
+    ContextProvider,
+    Html,
+    NodeRef,
+    Properties,
+};
+
+pub use diff_editor::*;
+pub use hooks::*;
+pub use tabbed_editor::*;
+pub use widgets::*;
+
+mod diff_editor;
+mod events;
+mod hooks;
+mod tabbed_editor;
+mod widgets;
 
 #[derive(Clone, Debug, PartialEq, Properties)]
 pub struct CodeEditorProps<OPT: std::cmp::PartialEq + Clone + Into<IStandaloneEditorConstructionOptions> = IStandaloneEditorConstructionOptions> {
     #[prop_or_default]
     pub link: Option<CodeEditorLink>,
     /// Changing the options will cause the editor to be re-created.
+    ///
+    /// Use `language`, `theme`, `read_only` and `editor_options` for options
+    /// that can change without re-creating the editor.
     #[prop_or_default]
     pub options: Option<OPT>,
     #[prop_or_default]
     pub model: Option<TextModel>,
+    /// The language of the model shown in the editor.
+    #[prop_or_default]
+    pub language: Option<AttrValue>,
+    /// The theme used by the editor.
+    ///
+    /// Monaco only supports a single theme at a time, so this changes the
+    /// theme of all editors. The theme is applied through
+    /// [`ThemeRegistry::global`], which stops it from following the color
+    /// scheme.
+    #[prop_or_default]
+    pub theme: Option<AttrValue>,
+    #[prop_or_default]
+    pub read_only: Option<bool>,
+    /// Options that are applied to the existing editor when they change.
+    #[prop_or_default]
+    pub editor_options: Option<EditorOptions>,
+    /// The text of the editor.
+    ///
+    /// If this is given the editor is controlled: whenever it changes, the
+    /// model is edited to match it, keeping the cursor and undo history
+    /// intact.
+    #[prop_or_default]
+    pub value: Option<AttrValue>,
+    /// Called with the text of the editor whenever the user changes it.
+    #[prop_or_default]
+    pub on_change: Callback<String>,
+    /// Only call `on_change` once the text hasn't changed for this many
+    /// milliseconds.
+    #[prop_or_default]
+    pub on_change_debounce_ms: Option<u32>,
+    /// Called on a "contextmenu".
+    #[prop_or_default]
+    pub on_context_menu: Option<Callback<IEditorMouseEvent>>,
+    /// Called when the text inside this editor lost focus (i.e. cursor stops blinking).
+    #[prop_or_default]
+    pub on_did_blur_editor_text: Option<Callback<()>>,
+    /// Called when the text inside this editor or an editor widget lost focus.
+    #[prop_or_default]
+    pub on_did_blur_editor_widget: Option<Callback<()>>,
+    /// Called when the configuration of the editor has changed. (e.g. editor.updateOptions())
+    #[prop_or_default]
+    pub on_did_change_configuration: Option<Callback<ConfigurationChangedEvent>>,
+    /// Called when the cursor position has changed.
+    #[prop_or_default]
+    pub on_did_change_cursor_position: Option<Callback<ICursorPositionChangedEvent>>,
+    /// Called when the cursor selection has changed.
+    #[prop_or_default]
+    pub on_did_change_cursor_selection: Option<Callback<ICursorSelectionChangedEvent>>,
+    /// Called when the model of this editor has changed (e.g. editor.setModel()).
+    #[prop_or_default]
+    pub on_did_change_model: Option<Callback<IModelChangedEvent>>,
+    /// Called when the content of the current model has changed.
+    #[prop_or_default]
+    pub on_did_change_model_content: Option<Callback<IModelContentChangedEvent>>,
+    /// Called when the decorations of the current model have changed.
+    #[prop_or_default]
+    pub on_did_change_model_decorations: Option<Callback<JsValue>>,
+    /// Called when the language of the current model has changed.
+    #[prop_or_default]
+    pub on_did_change_model_language: Option<Callback<IModelLanguageChangedEvent>>,
+    /// Called when the language configuration of the current model has changed.
+    #[prop_or_default]
+    pub on_did_change_model_language_configuration: Option<Callback<JsValue>>,
+    /// Called when the options of the current model has changed.
+    #[prop_or_default]
+    pub on_did_change_model_options: Option<Callback<IModelOptionsChangedEvent>>,
+    /// Called when the content width or content height in the editor has changed.
+    #[prop_or_default]
+    pub on_did_content_size_change: Option<Callback<IContentSizeChangedEvent>>,
+    /// Called when the editor has been disposed.
+    #[prop_or_default]
+    pub on_did_dispose: Option<Callback<()>>,
+    /// Called when the text inside this editor gained focus (i.e. cursor starts blinking).
+    #[prop_or_default]
+    pub on_did_focus_editor_text: Option<Callback<()>>,
+    /// Called when the text inside this editor or an editor widget gained focus.
+    #[prop_or_default]
+    pub on_did_focus_editor_widget: Option<Callback<()>>,
+    /// Called when the layout of the editor has changed.
+    #[prop_or_default]
+    pub on_did_layout_change: Option<Callback<EditorLayoutInfo>>,
+    /// Called when users paste text in the editor.
+    #[prop_or_default]
+    pub on_did_paste: Option<Callback<IPasteEvent>>,
+    /// Called when the scroll in the editor has changed.
+    #[prop_or_default]
+    pub on_did_scroll_change: Option<Callback<IScrollEvent>>,
+    /// Called on a "keydown".
+    #[prop_or_default]
+    pub on_key_down: Option<Callback<IKeyboardEvent>>,
+    /// Called on a "keyup".
+    #[prop_or_default]
+    pub on_key_up: Option<Callback<IKeyboardEvent>>,
+    /// Called on a "mousedown".
+    #[prop_or_default]
+    pub on_mouse_down: Option<Callback<IEditorMouseEvent>>,
+    /// Called on a "mouseleave".
+    #[prop_or_default]
+    pub on_mouse_leave: Option<Callback<IEditorMouseEvent>>,
+    /// Called on a "mousemove".
+    #[prop_or_default]
+    pub on_mouse_move: Option<Callback<IEditorMouseEvent>>,
+    /// Called on a "mouseup".
+    #[prop_or_default]
+    pub on_mouse_up: Option<Callback<IEditorMouseEvent>>,
     /// This could be called multiple times if the `options` field changes.
     /// You can use this to initialise the editor
     #[prop_or_default]
     pub on_editor_created: Callback<CodeEditorLink>,
     #[prop_or_default]
     pub classes: Classes,
+    /// Components that attach to the editor, like [`EditorContentWidget`],
+    /// [`EditorOverlayWidget`] and [`EditorViewZone`].
+    #[prop_or_default]
+    pub children: Children,
 }
 
 type ModelCell = Rc<RefCell<Option<CodeEditorModel>>>;
 
+/// A callback scheduled with `setTimeout`.
+/// The callback is cancelled when dropped.
+#[derive(Debug)]
+struct Timeout {
+    id: i32,
+    _closure: Closure<dyn FnMut()>,
+}
+impl Timeout {
+    fn new(millis: u32, callback: impl FnOnce() + 'static) -> Self {
+        let closure = Closure::once(callback);
+        let id = web_sys::window()
+            .expect("no global window exists")
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                millis as i32,
+            )
+            .expect("failed to set timeout");
+        Self {
+            id,
+            _closure: closure,
+        }
+    }
+}
+impl Drop for Timeout {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            window.clear_timeout_with_handle(self.id);
+        }
+    }
+}
+
+/// State shared between [`CodeEditor`] and its content change listener.
+#[derive(Debug, Default)]
+struct ChangeState {
+    on_change: Callback<String>,
+    debounce_ms: Option<u32>,
+    /// Set while the `value` prop is applied so the change isn't reported
+    /// back.
+    applying_value: bool,
+    pending: Option<Timeout>,
+    /// The last value passed to `on_change`.
+    ///
+    /// A `value` prop equal to it echoes our own change, which may be older
+    /// than the model when the change was debounced.
+    last_emitted: Option<String>,
+}
+
 /// CodeEditor component.
 #[derive(Debug)]
 pub struct CodeEditor {
     node_ref: NodeRef,
     editor: ModelCell,
+    /// Incremented whenever the editor is created.
+    generation: usize,
+    change_state: Rc<RefCell<ChangeState>>,
+    change_listener: Option<DisposableClosure<dyn FnMut(IModelContentChangedEvent)>>,
+    event_listeners: EventListeners,
 }
 impl CodeEditor {
+    /// Edit the model of the editor to match the `value` prop.
+    fn apply_value(&self, ctx: &Context<Self>) {
+        let value = match &ctx.props().value {
+            Some(value) => value,
+            None => return,
+        };
+        let model = self
+            .editor
+            .borrow()
+            .as_ref()
+            .and_then(|editor| editor.get_model());
+        if let Some(model) = model {
+            let mut state = self.change_state.borrow_mut();
+            if state.last_emitted.as_deref() == Some(value.as_str()) {
+                return;
+            }
+            state.last_emitted = None;
+            state.applying_value = true;
+            drop(state);
+            model.apply_value(value);
+            self.change_state.borrow_mut().applying_value = false;
+        }
+    }
+
+    /// Set the language of the model shown in the editor to the `language`
+    /// prop.
+    fn apply_language(&self, ctx: &Context<Self>) {
+        if let Some(language) = &ctx.props().language {
+            let model = self
+                .editor
+                .borrow()
+                .as_ref()
+                .and_then(|editor| editor.get_model());
+            if let Some(model) = model {
+                model.set_language(language);
+            }
+        }
+    }
+
+    /// Apply the `read_only` and `editor_options` props to the editor.
+    fn apply_editor_options(&self, ctx: &Context<Self>) {
+        let CodeEditorProps {
+            read_only,
+            editor_options,
+            ..
+        } = ctx.props();
+        if let Some(editor) = &*self.editor.borrow() {
+            if let Some(options) = editor_options {
+                editor.update_options(&options.to_sys_options());
+            }
+            if let Some(read_only) = read_only {
+                editor.set_read_only(*read_only);
+            }
+        }
+    }
+
+    fn listen_for_changes(
+        &self,
+        editor: &CodeEditorModel,
+    ) -> DisposableClosure<dyn FnMut(IModelContentChangedEvent)> {
+        let editor_cell = Rc::clone(&self.editor);
+        let change_state = Rc::clone(&self.change_state);
+        editor.on_did_change_model_content(move |_| {
+            let mut state = change_state.borrow_mut();
+            if state.applying_value {
+                return;
+            }
+
+            let emit = {
+                let editor_cell = Rc::clone(&editor_cell);
+                let change_state = Rc::clone(&change_state);
+                move || {
+                    let value = editor_cell
+                        .borrow()
+                        .as_ref()
+                        .and_then(|editor| editor.get_model())
+                        .map(|model| model.get_value());
+                    if let Some(value) = value {
+                        let on_change = {
+                            let mut state = change_state.borrow_mut();
+                            state.last_emitted = Some(value.clone());
+                            state.on_change.clone()
+                        };
+                        on_change.emit(value);
+                    }
+                }
+            };
+            match state.debounce_ms {
+                // replacing the pending timeout cancels it
+                Some(millis) => state.pending = Some(Timeout::new(millis, emit)),
+                None => {
+                    drop(state);
+                    emit();
+                }
+            }
+        })
+    }
+
     fn emit_editor_created(&self, ctx: &Context<Self>) {
         let CodeEditorProps {
             link,
@@ -56,35 +366,72 @@ impl Component for CodeEditor {
         if let Some(editor_link) = &ctx.props().link {
             editor_link.connect(editor.clone());
         }
+        let props = ctx.props();
+        let change_state = ChangeState {
+            on_change: props.on_change.clone(),
+            debounce_ms: props.on_change_debounce_ms,
+            ..ChangeState::default()
+        };
         Self {
             node_ref: NodeRef::default(),
             editor,
+            generation: 0,
+            change_state: Rc::new(RefCell::new(change_state)),
+            change_listener: None,
+            event_listeners: EventListeners::default(),
         }
     }
 
+    fn update(&mut self, _ctx: &Context<Self>, _msg: Self::Message) -> bool {
+        // the editor was created, pass it on to the children
+        true
+    }
+
     fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
         // these are the new values
         let CodeEditorProps {
             link,
             options,
             model,
+            language,
+            theme,
+            read_only,
+            editor_options,
+            value,
+            on_change,
+            on_change_debounce_ms,
             on_editor_created: _,
+            children,
             ..
         } = &ctx.props();
 
         let mut should_render = false;
+        let mut should_recreate = false;
         if link != &old_props.link {
             // make sure to connect the new link to this component
             if let Some(link) = &link {
                 link.connect(self.editor.clone());
             }
         }
+        if on_change != &old_props.on_change
+            || on_change_debounce_ms != &old_props.on_change_debounce_ms
+        {
+            let mut state = self.change_state.borrow_mut();
+            state.on_change = on_change.clone();
+            state.debounce_ms = *on_change_debounce_ms;
+        }
+        if children != &old_props.children {
+            should_render = true;
+        }
         // changing options requires re-create
         if options != &old_props.options {
             should_render = true;
+            should_recreate = true;
         }
         // change the attached model
         if model != &old_props.model {
+            // the last emitted value belongs to the old model
+            self.change_state.borrow_mut().last_emitted = None;
             if let Some(editor) = &mut *self.editor.borrow_mut() {
                 match model {
                     Some(model) => editor.set_model(model),
@@ -94,11 +441,32 @@ impl Component for CodeEditor {
                 }
             }
         }
+        if language != &old_props.language || model != &old_props.model {
+            self.apply_language(ctx);
+        }
+        if theme != &old_props.theme {
+            if let Some(theme) = theme {
+                ThemeRegistry::global().set_theme(theme);
+            }
+        }
+        if read_only != &old_props.read_only || editor_options != &old_props.editor_options {
+            self.apply_editor_options(ctx);
+        }
+        // the listeners only bind events whose callback was added or removed
+        if let Some(editor) = &*self.editor.borrow() {
+            self.event_listeners.update(editor, ctx.props());
+        }
+        if value != &old_props.value || model != &old_props.model {
+            self.apply_value(ctx);
+        }
 
-        if should_render {
+        if should_recreate {
             // if we're gonna re-create the editor we need to clean up the old one first so
-            // as not to cause issues
+            // as not to cause issues. The listeners are dropped after the editor so
+            // `on_did_dispose` still fires.
             self.editor.replace(None);
+            self.change_listener = None;
+            self.event_listeners = EventListeners::default();
         }
 
         should_render
@@ -106,21 +474,33 @@ impl Component for CodeEditor {
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let Self {
-            node_ref, editor, ..
+            node_ref,
+            editor,
+            generation,
+            ..
         } = self;
         let props = ctx.props();
-
-        debug_assert!(
-            editor.borrow().is_none(),
-            "previous editor must be disposed before re-creating"
-        );
+        let context = CodeEditorContext {
+            editor: editor.clone(),
+            generation: *generation,
+        };
 
         html! {
-            <div ref={node_ref.clone()} class={props.classes.clone()} />
+            <>
+                <div ref={node_ref.clone()} class={props.classes.clone()} />
+                <ContextProvider<CodeEditorContext> {context}>
+                    { props.children.clone() }
+                </ContextProvider<CodeEditorContext>>
+            </>
         }
     }
 
     fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if self.editor.borrow().is_some() {
+            // only re-rendered for the children
+            return;
+        }
+
         let el = self
             .node_ref
             .cast::<HtmlElement>()
@@ -136,22 +516,57 @@ impl Component for CodeEditor {
             editor.set_model(model)
         }
 
+        self.change_listener = Some(self.listen_for_changes(&editor));
+        self.event_listeners.update(&editor, props);
+        if let Some(theme) = &props.theme {
+            ThemeRegistry::global().set_theme(theme);
+        }
+
         self.editor.replace(Some(editor));
+        self.apply_value(ctx);
+        self.apply_language(ctx);
+        self.apply_editor_options(ctx);
+        self.generation += 1;
+        ctx.link().send_message(());
         self.emit_editor_created(ctx);
     }
 }
 
+/// Context provided by [`CodeEditor`] to its children.
+///
+/// Use it to attach things to the editor from a child component.
+/// The context changes whenever the editor is re-created.
+#[derive(Clone, Debug)]
+pub struct CodeEditorContext {
+    editor: ModelCell,
+    generation: usize,
+}
+impl CodeEditorContext {
+    /// Get access to the underlying [`CodeEditor`](CodeEditorModel).
+    /// The return value is `None` if the editor hasn't been created yet.
+    pub fn with_editor<T>(&self, f: impl FnOnce(&CodeEditorModel) -> T) -> Option<T> {
+        self.editor.borrow().as_ref().map(f)
+    }
+}
+impl PartialEq for CodeEditorContext {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.editor, &other.editor) && self.generation == other.generation
+    }
+}
+
 /// Link to control a [`CodeEditor`].
+///
+/// Clones of a link stay connected to the same editor.
 #[derive(Clone, Debug, Default)]
-pub struct CodeEditorLink(RefCell<ModelCell>);
+pub struct CodeEditorLink(Rc<RefCell<ModelCell>>);
 
 impl CodeEditorLink {
     pub fn new() -> Self {
-        Self(RefCell::default())
+        Self::default()
     }
 
     fn new_connected(model_cell: ModelCell) -> Self {
-        Self(RefCell::new(model_cell))
+        Self(Rc::new(RefCell::new(model_cell)))
     }
 
     fn connect(&self, model_cell: ModelCell) {
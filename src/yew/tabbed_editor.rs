@@ -0,0 +1,507 @@
+use super::{CodeEditor, CodeEditorLink};
+use crate::{
+    api::{DisposableClosure, TextModel},
+    sys::{
+        editor::{
+            ICodeEditorViewState,
+            IModelContentChangedEvent,
+            IStandaloneCodeEditor,
+            IStandaloneEditorConstructionOptions,
+        },
+        KeyCode,
+        KeyMod,
+        Uri,
+    },
+};
+use std::collections::HashMap;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::MouseEvent;
+use yew::{classes, html, AttrValue, Callback, Classes, Component, Context, Html, Properties};
+
+/// A document shown in a [`TabbedEditor`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Document {
+    /// Uri of the document's model. Documents are identified by it.
+    pub uri: AttrValue,
+    pub language: Option<AttrValue>,
+    /// The text of the document when its model is created.
+    pub initial_value: AttrValue,
+    /// The title shown in the tab.
+    /// Defaults to the last segment of the uri.
+    pub title: Option<AttrValue>,
+}
+impl Document {
+    pub fn new(uri: impl Into<AttrValue>, initial_value: impl Into<AttrValue>) -> Self {
+        Self {
+            uri: uri.into(),
+            language: None,
+            initial_value: initial_value.into(),
+            title: None,
+        }
+    }
+
+    pub fn with_language(mut self, language: impl Into<AttrValue>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<AttrValue>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    fn title(&self) -> &str {
+        match &self.title {
+            Some(title) => title,
+            None => self
+                .uri
+                .rsplit('/')
+                .find(|segment| !segment.is_empty())
+                .unwrap_or(&self.uri),
+        }
+    }
+}
+
+/// A document saved in a [`TabbedEditor`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedDocument {
+    pub uri: AttrValue,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct TabbedEditorProps {
+    pub documents: Vec<Document>,
+    /// Uri of the document to show.
+    /// Defaults to the first document.
+    #[prop_or_default]
+    pub active: Option<AttrValue>,
+    /// Changing the options will cause the editor to be re-created.
+    #[prop_or_default]
+    pub options: Option<IStandaloneEditorConstructionOptions>,
+    /// Called with the uri of a document when its tab is selected.
+    #[prop_or_default]
+    pub on_activate: Callback<AttrValue>,
+    /// Called when the user presses Ctrl/Cmd + S.
+    /// The document is no longer dirty afterwards.
+    #[prop_or_default]
+    pub on_save: Callback<SavedDocument>,
+    /// Called with the uri of a document when its tab is closed.
+    /// Remove the document from `documents` to actually close it.
+    #[prop_or_default]
+    pub on_close: Callback<AttrValue>,
+    /// Called with the uri of a document and whether it has unsaved changes
+    /// whenever that changes.
+    #[prop_or_default]
+    pub on_dirty_change: Callback<(AttrValue, bool)>,
+    /// Called with the uri of a document and the error if its model couldn't
+    /// be created. The document isn't shown.
+    #[prop_or_default]
+    pub on_open_error: Callback<(AttrValue, JsValue)>,
+    #[prop_or_default]
+    pub classes: Classes,
+}
+
+/// State kept for every open document.
+#[derive(Debug)]
+struct DocumentState {
+    model: TextModel,
+    /// Whether the model was created by the editor and should be disposed
+    /// when the document is closed.
+    owned: bool,
+    view_state: Option<ICodeEditorViewState>,
+    /// The alternative version id of the model when it was last saved.
+    /// Undoing back to the saved text makes the document clean again.
+    saved_version_id: f64,
+    dirty: bool,
+    _change_listener: DisposableClosure<dyn FnMut(IModelContentChangedEvent)>,
+}
+impl Drop for DocumentState {
+    fn drop(&mut self) {
+        if self.owned {
+            self.model.as_ref().dispose();
+        }
+    }
+}
+
+pub enum TabbedEditorMsg {
+    EditorCreated(CodeEditorLink),
+    Activate(AttrValue),
+    Close(AttrValue),
+    Save,
+    ContentChanged(AttrValue),
+}
+
+/// Editor showing multiple documents in tabs.
+///
+/// A single [`CodeEditor`] is used for all documents. Switching tabs swaps the
+/// model of the editor and restores the cursor and scroll position of the
+/// document.
+///
+/// The tabs can be styled using the `monaco-tabbed-editor-*` CSS classes.
+/// The active tab has the `active` class and tabs with unsaved changes the
+/// `dirty` class.
+#[derive(Debug)]
+pub struct TabbedEditor {
+    link: CodeEditorLink,
+    documents: HashMap<AttrValue, DocumentState>,
+    active: Option<AttrValue>,
+    _save_command: Option<Closure<dyn FnMut()>>,
+}
+impl TabbedEditor {
+    /// Create state for new documents and drop the state of removed ones.
+    fn sync_documents(&mut self, ctx: &Context<Self>) {
+        let documents = &ctx.props().documents;
+        self.documents
+            .retain(|uri, _| documents.iter().any(|document| &document.uri == uri));
+        for document in documents {
+            if !self.documents.contains_key(&document.uri) {
+                match Self::open_document(ctx, document) {
+                    Ok(state) => {
+                        self.documents.insert(document.uri.clone(), state);
+                    }
+                    Err(err) => ctx.props().on_open_error.emit((document.uri.clone(), err)),
+                }
+            }
+        }
+    }
+
+    fn open_document(ctx: &Context<Self>, document: &Document) -> Result<DocumentState, JsValue> {
+        let Document {
+            uri,
+            language,
+            initial_value,
+            ..
+        } = document;
+        let js_uri = Uri::parse(uri, false);
+        let (model, owned) = match TextModel::get(&js_uri) {
+            Some(model) => (model, false),
+            None => {
+                let model = TextModel::create(initial_value, language.as_deref(), Some(&js_uri))?;
+                (model, true)
+            }
+        };
+
+        let change_listener = {
+            let uri = uri.clone();
+            let callback = ctx.link().callback(TabbedEditorMsg::ContentChanged);
+            model.on_did_change_content(move |_| callback.emit(uri.clone()))
+        };
+        Ok(DocumentState {
+            saved_version_id: model.as_ref().get_alternative_version_id(),
+            model,
+            owned,
+            view_state: None,
+            dirty: false,
+            _change_listener: change_listener,
+        })
+    }
+
+    /// The document to show if the active one was closed.
+    fn fallback_active(&self, ctx: &Context<Self>) -> Option<AttrValue> {
+        let props = ctx.props();
+        fallback_active(props.active.as_ref(), &props.documents, |uri| {
+            self.documents.contains_key(uri)
+        })
+    }
+
+    /// Show the active document in the editor.
+    fn show_active(&self) {
+        let state = self.active.as_ref().and_then(|uri| self.documents.get(uri));
+        self.link.with_editor(|editor| match state {
+            Some(state) => {
+                editor.set_model(&state.model);
+                if let Some(view_state) = &state.view_state {
+                    editor.as_ref().restore_view_state(view_state);
+                }
+            }
+            None => {
+                editor.detach_model();
+            }
+        });
+    }
+
+    /// Remember the cursor and scroll position of the active document.
+    fn save_active_view_state(&mut self) {
+        let view_state = self
+            .link
+            .with_editor(|editor| editor.as_ref().save_view_state())
+            .flatten();
+        let documents = &mut self.documents;
+        let state = self.active.as_ref().and_then(|uri| documents.get_mut(uri));
+        if let Some(state) = state {
+            state.view_state = view_state;
+        }
+    }
+
+    fn switch_to(&mut self, uri: Option<AttrValue>) -> bool {
+        if uri == self.active {
+            return false;
+        }
+        self.save_active_view_state();
+        self.active = uri;
+        self.show_active();
+        true
+    }
+
+    /// Update the dirty state of a document and report whether it changed.
+    fn update_dirty(&mut self, ctx: &Context<Self>, uri: &AttrValue) -> bool {
+        let state = match self.documents.get_mut(uri) {
+            Some(state) => state,
+            None => return false,
+        };
+        let dirty = state.model.as_ref().get_alternative_version_id() != state.saved_version_id;
+        if dirty == state.dirty {
+            return false;
+        }
+        state.dirty = dirty;
+        ctx.props().on_dirty_change.emit((uri.clone(), dirty));
+        true
+    }
+
+    fn bind_save_command(&mut self, ctx: &Context<Self>) {
+        let callback = ctx.link().callback(|_| TabbedEditorMsg::Save);
+        let closure = Closure::<dyn FnMut()>::new(move || callback.emit(()));
+        self.link.with_editor(|editor| {
+            let js_editor: &IStandaloneCodeEditor = editor.as_ref();
+            let keybinding = KeyMod::ctrl_cmd() as u32 | KeyCode::KeyS.to_value();
+            js_editor.add_command(keybinding.into(), closure.as_ref().unchecked_ref(), None);
+        });
+        // commands can't be removed, the closure lives as long as the editor
+        self._save_command = Some(closure);
+    }
+
+    fn view_tab(&self, ctx: &Context<Self>, document: &Document) -> Html {
+        let uri = &document.uri;
+        let is_active = self.active.as_ref() == Some(uri);
+        let is_dirty = self.documents.get(uri).is_some_and(|state| state.dirty);
+
+        let onclick = {
+            let uri = uri.clone();
+            ctx.link()
+                .callback(move |_: MouseEvent| TabbedEditorMsg::Activate(uri.clone()))
+        };
+        let onclose = {
+            let uri = uri.clone();
+            ctx.link().callback(move |event: MouseEvent| {
+                // don't activate the tab that is being closed
+                event.stop_propagation();
+                TabbedEditorMsg::Close(uri.clone())
+            })
+        };
+
+        html! {
+            <div
+                key={uri.to_string()}
+                class={classes!(
+                    "monaco-tabbed-editor-tab",
+                    is_active.then_some("active"),
+                    is_dirty.then_some("dirty"),
+                )}
+                role="tab"
+                aria-selected={is_active.to_string()}
+                title={uri.clone()}
+                {onclick}
+            >
+                <span class="monaco-tabbed-editor-tab-title">{ document.title() }</span>
+                if is_dirty {
+                    <span class="monaco-tabbed-editor-tab-dirty">{ "\u{25cf}" }</span>
+                }
+                <button class="monaco-tabbed-editor-tab-close" aria-label="Close" onclick={onclose}>
+                    { "\u{d7}" }
+                </button>
+            </div>
+        }
+    }
+}
+/// The requested document if it's open, otherwise the first open document.
+fn fallback_active(
+    requested: Option<&AttrValue>,
+    documents: &[Document],
+    is_open: impl Fn(&AttrValue) -> bool,
+) -> Option<AttrValue> {
+    requested
+        .filter(|uri| is_open(uri))
+        .or_else(|| {
+            documents
+                .iter()
+                .map(|document| &document.uri)
+                .find(|uri| is_open(uri))
+        })
+        .cloned()
+}
+
+impl Component for TabbedEditor {
+    type Message = TabbedEditorMsg;
+    type Properties = TabbedEditorProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut this = Self {
+            link: CodeEditorLink::new(),
+            documents: HashMap::new(),
+            active: None,
+            _save_command: None,
+        };
+        this.sync_documents(ctx);
+        this.active = this.fallback_active(ctx);
+        this
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            TabbedEditorMsg::EditorCreated(link) => {
+                self.link = link;
+                self.bind_save_command(ctx);
+                self.show_active();
+                false
+            }
+            TabbedEditorMsg::Activate(uri) => {
+                ctx.props().on_activate.emit(uri.clone());
+                self.switch_to(Some(uri))
+            }
+            TabbedEditorMsg::Close(uri) => {
+                ctx.props().on_close.emit(uri);
+                false
+            }
+            TabbedEditorMsg::Save => {
+                let uri = match &self.active {
+                    Some(uri) => uri.clone(),
+                    None => return false,
+                };
+                let value = match self.documents.get_mut(&uri) {
+                    Some(state) => {
+                        state.saved_version_id = state.model.as_ref().get_alternative_version_id();
+                        state.model.get_value()
+                    }
+                    None => return false,
+                };
+                ctx.props().on_save.emit(SavedDocument {
+                    uri: uri.clone(),
+                    value,
+                });
+                self.update_dirty(ctx, &uri)
+            }
+            TabbedEditorMsg::ContentChanged(uri) => self.update_dirty(ctx, &uri),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let TabbedEditorProps {
+            documents,
+            active,
+            options,
+            ..
+        } = ctx.props();
+
+        if options != &old_props.options {
+            // the editor is re-created, keep the position in the active document
+            self.save_active_view_state();
+        }
+
+        if documents != &old_props.documents {
+            // detach the model before it's disposed
+            let active_closed = self
+                .active
+                .as_ref()
+                .is_some_and(|uri| !documents.iter().any(|document| &document.uri == uri));
+            if active_closed {
+                self.active = None;
+                self.show_active();
+            }
+            self.sync_documents(ctx);
+        }
+
+        let requested = if active != &old_props.active {
+            active
+                .clone()
+                .filter(|uri| self.documents.contains_key(uri))
+        } else {
+            None
+        };
+        match requested {
+            Some(uri) => {
+                self.switch_to(Some(uri));
+            }
+            None if self.active.is_none() => {
+                let uri = self.fallback_active(ctx);
+                self.switch_to(uri);
+            }
+            None => {}
+        }
+
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        html! {
+            <div class={classes!("monaco-tabbed-editor", props.classes.clone())}>
+                <div class="monaco-tabbed-editor-tabs" role="tablist">
+                    {
+                        for props
+                            .documents
+                            .iter()
+                            .filter(|document| self.documents.contains_key(&document.uri))
+                            .map(|document| self.view_tab(ctx, document))
+                    }
+                </div>
+                <CodeEditor
+                    classes="monaco-tabbed-editor-editor"
+                    link={self.link.clone()}
+                    options={props.options.clone()}
+                    on_editor_created={ctx.link().callback(TabbedEditorMsg::EditorCreated)}
+                />
+            </div>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn link_given_to_the_editor_stays_connected() {
+        let link = CodeEditorLink::new();
+        let editor = Rc::new(RefCell::new(None));
+        // the editor connects the clone it received as a prop
+        link.clone().connect(Rc::clone(&editor));
+        assert_eq!(link, CodeEditorLink::new_connected(editor));
+    }
+
+    #[test]
+    fn switching_tabs() {
+        let documents = [
+            Document::new("file:///a.rs", ""),
+            Document::new("file:///b.rs", ""),
+            Document::new("file:///c.rs", ""),
+        ];
+        let a = AttrValue::from("file:///a.rs");
+        let b = AttrValue::from("file:///b.rs");
+        let c = AttrValue::from("file:///c.rs");
+        let mut open = vec![a.clone(), b.clone(), c.clone()];
+
+        // the first document is shown by default
+        let active = fallback_active(None, &documents, |uri| open.contains(uri));
+        assert_eq!(active.as_ref(), Some(&a));
+        let active = fallback_active(Some(&c), &documents, |uri| open.contains(uri));
+        assert_eq!(active.as_ref(), Some(&c));
+
+        // closing the active tab switches to the first one left
+        open.retain(|uri| uri != &a);
+        let active = fallback_active(Some(&a), &documents, |uri| open.contains(uri));
+        assert_eq!(active.as_ref(), Some(&b));
+
+        // documents that failed to open are skipped
+        open.retain(|uri| uri != &b);
+        let active = fallback_active(None, &documents, |uri| open.contains(uri));
+        assert_eq!(active.as_ref(), Some(&c));
+
+        open.clear();
+        assert_eq!(
+            fallback_active(Some(&c), &documents, |uri| open.contains(uri)),
+            None
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Components rendering Yew children inside of the editor.
+use super::CodeEditorContext;
+use crate::{
+    api::{
+        ContentWidget,
+        ContentWidgetHandle,
+        ContentWidgetPosition,
+        OverlayWidget,
+        OverlayWidgetHandle,
+        ViewZone,
+        ViewZoneHandle,
+    },
+    sys::editor::OverlayWidgetPositionPreference,
+};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+use yew::{
+    context::ContextHandle,
+    create_portal,
+    html,
+    AttrValue,
+    Callback,
+    Children,
+    Component,
+    Context,
+    Html,
+    Properties,
+};
+
+/// Create the element the children of a component are rendered into.
+fn create_host() -> HtmlElement {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .expect("no global document exists")
+        .create_element("div")
+        .expect("failed to create element")
+        .unchecked_into()
+}
+
+/// Get the given id or generate a unique one.
+fn widget_id(id: &Option<AttrValue>, kind: &str) -> String {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    match id {
+        Some(id) => id.to_string(),
+        None => format!(
+            "monaco-yew-{}-{}",
+            kind,
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ),
+    }
+}
+
+/// Subscribe to the [`CodeEditorContext`] of the surrounding
+/// [`CodeEditor`](super::CodeEditor).
+fn subscribe<COMP>(
+    ctx: &Context<COMP>,
+) -> Option<(CodeEditorContext, ContextHandle<CodeEditorContext>)>
+where
+    COMP: Component<Message = CodeEditorContext>,
+{
+    ctx.link().context(ctx.link().callback(|context| context))
+}
+
+fn view_portal(host: &HtmlElement, children: &Children) -> Html {
+    create_portal(html! { <>{ children.clone() }</> }, host.clone().into())
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct EditorContentWidgetProps {
+    /// Unique id of the widget. A unique id is generated if it isn't given.
+    #[prop_or_default]
+    pub id: Option<AttrValue>,
+    /// The widget is placed off screen if this is `None`.
+    #[prop_or_default]
+    pub position: Option<ContentWidgetPosition>,
+    #[prop_or_default]
+    pub allow_editor_overflow: bool,
+    #[prop_or_default]
+    pub suppress_mouse_down: bool,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+struct PortalContentWidget {
+    id: String,
+    host: HtmlElement,
+    position: Rc<RefCell<Option<ContentWidgetPosition>>>,
+    allow_editor_overflow: bool,
+    suppress_mouse_down: bool,
+}
+impl ContentWidget for PortalContentWidget {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn dom_node(&self) -> HtmlElement {
+        self.host.clone()
+    }
+
+    fn position(&self) -> Option<ContentWidgetPosition> {
+        self.position.borrow().clone()
+    }
+
+    fn allow_editor_overflow(&self) -> bool {
+        self.allow_editor_overflow
+    }
+
+    fn suppress_mouse_down(&self) -> bool {
+        self.suppress_mouse_down
+    }
+}
+
+/// Renders its children in a content widget of the surrounding
+/// [`CodeEditor`](super::CodeEditor).
+///
+/// ```ignore
+/// html! {
+///     <CodeEditor options={options}>
+///         <EditorContentWidget position={position}>
+///             <ReviewComment />
+///         </EditorContentWidget>
+///     </CodeEditor>
+/// }
+/// ```
+pub struct EditorContentWidget {
+    host: HtmlElement,
+    id: String,
+    position: Rc<RefCell<Option<ContentWidgetPosition>>>,
+    context: Option<CodeEditorContext>,
+    _context_handle: Option<ContextHandle<CodeEditorContext>>,
+    handle: Option<ContentWidgetHandle<PortalContentWidget>>,
+}
+impl EditorContentWidget {
+    fn attach(&mut self, ctx: &Context<Self>) {
+        let props = ctx.props();
+        // remove the widget from the previous editor first
+        self.handle = None;
+        self.handle = self.context.as_ref().and_then(|context| {
+            context.with_editor(|editor| {
+                editor.add_content_widget(PortalContentWidget {
+                    id: self.id.clone(),
+                    host: self.host.clone(),
+                    position: Rc::clone(&self.position),
+                    allow_editor_overflow: props.allow_editor_overflow,
+                    suppress_mouse_down: props.suppress_mouse_down,
+                })
+            })
+        });
+    }
+}
+impl Component for EditorContentWidget {
+    type Message = CodeEditorContext;
+    type Properties = EditorContentWidgetProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        let (context, context_handle) = subscribe(ctx).unzip();
+        let mut this = Self {
+            host: create_host(),
+            id: widget_id(&props.id, "content-widget"),
+            position: Rc::new(RefCell::new(props.position.clone())),
+            context,
+            _context_handle: context_handle,
+            handle: None,
+        };
+        this.attach(ctx);
+        this
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, context: Self::Message) -> bool {
+        self.context = Some(context);
+        self.attach(ctx);
+        false
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let EditorContentWidgetProps {
+            id,
+            position,
+            allow_editor_overflow,
+            suppress_mouse_down,
+            children,
+        } = ctx.props();
+
+        if id != &old_props.id
+            || allow_editor_overflow != &old_props.allow_editor_overflow
+            || suppress_mouse_down != &old_props.suppress_mouse_down
+        {
+            if id != &old_props.id {
+                self.id = widget_id(id, "content-widget");
+            }
+            self.position.replace(position.clone());
+            self.attach(ctx);
+        } else if position != &old_props.position {
+            self.position.replace(position.clone());
+            if let Some(handle) = &self.handle {
+                handle.layout();
+            }
+        }
+
+        children != &old_props.children
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        view_portal(&self.host, &ctx.props().children)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct EditorOverlayWidgetProps {
+    /// Unique id of the widget. A unique id is generated if it isn't given.
+    #[prop_or_default]
+    pub id: Option<AttrValue>,
+    /// The children are responsible for placing themselves if this is `None`.
+    #[prop_or_default]
+    pub position: Option<OverlayWidgetPositionPreference>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+struct PortalOverlayWidget {
+    id: String,
+    host: HtmlElement,
+    position: Rc<RefCell<Option<OverlayWidgetPositionPreference>>>,
+}
+impl OverlayWidget for PortalOverlayWidget {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn dom_node(&self) -> HtmlElement {
+        self.host.clone()
+    }
+
+    fn position(&self) -> Option<OverlayWidgetPositionPreference> {
+        *self.position.borrow()
+    }
+}
+
+/// Renders its children in an overlay widget of the surrounding
+/// [`CodeEditor`](super::CodeEditor).
+pub struct EditorOverlayWidget {
+    host: HtmlElement,
+    id: String,
+    position: Rc<RefCell<Option<OverlayWidgetPositionPreference>>>,
+    context: Option<CodeEditorContext>,
+    _context_handle: Option<ContextHandle<CodeEditorContext>>,
+    handle: Option<OverlayWidgetHandle<PortalOverlayWidget>>,
+}
+impl EditorOverlayWidget {
+    fn attach(&mut self) {
+        // remove the widget from the previous editor first
+        self.handle = None;
+        self.handle = self.context.as_ref().and_then(|context| {
+            context.with_editor(|editor| {
+                editor.add_overlay_widget(PortalOverlayWidget {
+                    id: self.id.clone(),
+                    host: self.host.clone(),
+                    position: Rc::clone(&self.position),
+                })
+            })
+        });
+    }
+}
+impl Component for EditorOverlayWidget {
+    type Message = CodeEditorContext;
+    type Properties = EditorOverlayWidgetProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        let (context, context_handle) = subscribe(ctx).unzip();
+        let mut this = Self {
+            host: create_host(),
+            id: widget_id(&props.id, "overlay-widget"),
+            position: Rc::new(RefCell::new(props.position)),
+            context,
+            _context_handle: context_handle,
+            handle: None,
+        };
+        this.attach();
+        this
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, context: Self::Message) -> bool {
+        self.context = Some(context);
+        self.attach();
+        false
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let EditorOverlayWidgetProps {
+            id,
+            position,
+            children,
+        } = ctx.props();
+
+        self.position.replace(*position);
+        if id != &old_props.id {
+            self.id = widget_id(id, "overlay-widget");
+            self.attach();
+        } else if position != &old_props.position {
+            if let Some(handle) = &self.handle {
+                handle.layout();
+            }
+        }
+
+        children != &old_props.children
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        view_portal(&self.host, &ctx.props().children)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Properties)]
+pub struct EditorViewZoneProps {
+    /// The line number after which the zone appears.
+    /// Use 0 to place the zone before the first line.
+    pub after_line_number: u32,
+    #[prop_or_default]
+    pub after_column: Option<u32>,
+    #[prop_or_default]
+    pub height_in_lines: Option<f64>,
+    /// Takes precedence over `height_in_lines`.
+    #[prop_or_default]
+    pub height_in_px: Option<f64>,
+    #[prop_or_default]
+    pub suppress_mouse_down: bool,
+    /// Called with the top of the zone relative to the editor.
+    #[prop_or_default]
+    pub on_dom_node_top: Callback<f64>,
+    /// Called with the height of the zone in pixels.
+    #[prop_or_default]
+    pub on_computed_height: Callback<f64>,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Renders its children in a view zone of the surrounding
+/// [`CodeEditor`](super::CodeEditor).
+///
+/// Monaco removes view zones when the model of the editor changes.
+pub struct EditorViewZone {
+    host: HtmlElement,
+    on_dom_node_top: Rc<RefCell<Callback<f64>>>,
+    on_computed_height: Rc<RefCell<Callback<f64>>>,
+    context: Option<CodeEditorContext>,
+    _context_handle: Option<ContextHandle<CodeEditorContext>>,
+    handle: Option<ViewZoneHandle>,
+}
+impl EditorViewZone {
+    fn attach(&mut self, ctx: &Context<Self>) {
+        let props = ctx.props();
+        // remove the zone from the previous editor first
+        self.handle = None;
+
+        let on_dom_node_top = Rc::clone(&self.on_dom_node_top);
+        let on_computed_height = Rc::clone(&self.on_computed_height);
+        let zone = ViewZone {
+            after_column: props.after_column,
+            height_in_lines: props.height_in_lines,
+            height_in_px: props.height_in_px,
+            suppress_mouse_down: Some(props.suppress_mouse_down),
+            ..ViewZone::new(props.after_line_number, self.host.clone())
+        }
+        .with_on_dom_node_top(move |top| on_dom_node_top.borrow().emit(top))
+        .with_on_computed_height(move |height| on_computed_height.borrow().emit(height));
+
+        self.handle = self.context.as_ref().and_then(|context| {
+            context.with_editor(|editor| editor.view_zones(|accessor| accessor.add_zone(zone)))
+        });
+    }
+}
+impl Component for EditorViewZone {
+    type Message = CodeEditorContext;
+    type Properties = EditorViewZoneProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        let (context, context_handle) = subscribe(ctx).unzip();
+        let mut this = Self {
+            host: create_host(),
+            on_dom_node_top: Rc::new(RefCell::new(props.on_dom_node_top.clone())),
+            on_computed_height: Rc::new(RefCell::new(props.on_computed_height.clone())),
+            context,
+            _context_handle: context_handle,
+            handle: None,
+        };
+        this.attach(ctx);
+        this
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, context: Self::Message) -> bool {
+        self.context = Some(context);
+        self.attach(ctx);
+        false
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        let EditorViewZoneProps {
+            after_line_number,
+            after_column,
+            height_in_lines,
+            height_in_px,
+            suppress_mouse_down,
+            on_dom_node_top,
+            on_computed_height,
+            children,
+        } = ctx.props();
+
+        self.on_dom_node_top.replace(on_dom_node_top.clone());
+        self.on_computed_height.replace(on_computed_height.clone());
+
+        if let Some(handle) = &self.handle {
+            if suppress_mouse_down != &old_props.suppress_mouse_down {
+                self.attach(ctx);
+            } else {
+                if after_line_number != &old_props.after_line_number
+                    || after_column != &old_props.after_column
+                {
+                    handle.set_after(*after_line_number, *after_column);
+                }
+                if height_in_lines != &old_props.height_in_lines
+                    || height_in_px != &old_props.height_in_px
+                {
+                    match (height_in_px, height_in_lines) {
+                        (Some(px), _) => handle.set_height_in_px(*px),
+                        (None, Some(lines)) => handle.set_height_in_lines(*lines),
+                        // the default height can only be restored by adding the zone again
+                        (None, None) => self.attach(ctx),
+                    }
+                }
+            }
+        }
+
+        children != &old_props.children
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        view_portal(&self.host, &ctx.props().children)
+    }
+}
@@ -193,6 +193,17 @@ _PATTERN_OBJECT_OPEN = re.compile(
 )
 
 
+# Additional derives for object types that the `api` module clones or compares.
+_EXTRA_DERIVES = {
+    "CancellationToken": ("Clone",),
+    "Command": ("Clone", "Eq", "PartialEq"),
+    "IDimension": ("Clone", "Eq", "PartialEq"),
+    "IRange": ("Clone", "Eq", "PartialEq"),
+    "ITextModel": ("Clone", "Eq", "PartialEq"),
+    "InlineCompletions": ("Clone", "Eq", "PartialEq"),
+}
+
+
 @dataclasses.dataclass()
 class JsObject(Documented, ToRust):
     ident: str
@@ -244,12 +255,15 @@ class JsObject(Documented, ToRust):
 
         return ""
 
+    def derives(self) -> List[str]:
+        return sorted(("Debug", *_EXTRA_DERIVES.get(self.ident, ())))
+
     def to_rust(self, ctx: Context) -> str:
         ctx = ctx.push(self.ident)
         return helpers.join_nonempty_lines(
             (
                 self.rust_documentation(),
-                f"#[derive(Debug)]",
+                f"#[derive({', '.join(self.derives())})]",
                 self.wasm_bindgen_attr(),
                 f"pub type {self.ident};",
                 *(member.to_rust(ctx) for member in self.members),